@@ -0,0 +1,65 @@
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+
+mod pipeline;
+
+/// Prove, set up, verify and export BitVMX ZK proofs
+#[derive(Parser)]
+#[command(name = "bitvmx-zk", version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    #[command(flatten)]
+    Host(host::cli::Commands),
+
+    #[command(flatten)]
+    Verifier(verifier::cli::Commands),
+
+    /// Run every stage from dump-id to template-proof, resuming from the state file
+    Pipeline {
+        /// TOML pipeline config file
+        #[arg(short, long, value_name = "FILE")]
+        config: String,
+
+        /// Ignore the recorded state and run every stage again
+        #[arg(long)]
+        restart: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    host::cli::init_logging();
+
+    let cli = Cli::parse();
+
+    match &cli.command {
+        Some(Commands::Host(command)) => {
+            if let Err(e) = host::cli::execute(command) {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(e.exit_code());
+            }
+        }
+        Some(Commands::Verifier(command)) => {
+            if let Err(e) = verifier::cli::execute(command) {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(e.exit_code());
+            }
+        }
+        Some(Commands::Pipeline { config, restart }) => {
+            if let Err(e) = pipeline::run_pipeline(config, *restart) {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(e.exit_code());
+            }
+        }
+        None => {
+            println!("No command provided");
+        }
+    }
+
+    ExitCode::SUCCESS
+}
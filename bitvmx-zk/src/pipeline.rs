@@ -0,0 +1,393 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use host::cache::ProofCache;
+use host::error::HostError;
+use host::prover::{new_prover, ProveOptions, ProverKind};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use verifier::error::VerifierError;
+use verifier::format::{get_image_id, get_journal_from_receipt, get_seal};
+use verifier::ProofCheck;
+use zk_result::ResultType;
+
+/// Error of a pipeline run, with the exit code of the host or verifier error
+/// it comes from
+#[derive(Debug)]
+pub enum PipelineError {
+    Host(HostError),
+    Verifier(VerifierError),
+    Stage(&'static str, Box<PipelineError>),
+}
+
+impl PipelineError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            PipelineError::Host(e) => e.exit_code(),
+            PipelineError::Verifier(e) => e.exit_code(),
+            PipelineError::Stage(_, e) => e.exit_code(),
+        }
+    }
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineError::Host(e) => write!(f, "{}", e),
+            PipelineError::Verifier(e) => write!(f, "{}", e),
+            PipelineError::Stage(stage, e) => write!(f, "Stage {} failed: {}", stage, e),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+impl From<HostError> for PipelineError {
+    fn from(e: HostError) -> Self {
+        PipelineError::Host(e)
+    }
+}
+
+impl From<VerifierError> for PipelineError {
+    fn from(e: VerifierError) -> Self {
+        PipelineError::Verifier(e)
+    }
+}
+
+/// Pipeline configuration read from a TOML file
+#[derive(Deserialize)]
+pub struct PipelineConfig {
+    /// Guest package to prove, it must be embedded by the methods crate
+    pub guest: String,
+    /// Text file with the u32 input of the guest
+    pub input_file: String,
+    /// Where the stage status and artifact hashes are recorded
+    pub state_file: Option<String>,
+    /// Proof cache directory shared by the proving stages
+    pub cache: Option<String>,
+    /// Prover backend, `local` when missing
+    pub prover: Option<ProverKind>,
+    /// Path of the r0vm binary used by the r0vm prover
+    pub r0vm_path: Option<String>,
+    /// Options of the prove-stark stage
+    #[serde(default)]
+    pub options: ProveOptions,
+    pub templates: TemplateConfig,
+    pub outputs: OutputConfig,
+}
+
+#[derive(Deserialize)]
+pub struct TemplateConfig {
+    /// Initial constants template of the C verifier
+    pub constants: String,
+}
+
+#[derive(Deserialize)]
+pub struct OutputConfig {
+    pub image_id: String,
+    pub receipt: String,
+    pub seal: String,
+    /// Template with the verifier constants but not the proof
+    pub intermediate: String,
+    /// Template with the verifier constants and the proof
+    pub constants: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum StageStatus {
+    Done,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StageState {
+    pub status: StageStatus,
+    /// Hash of every file the stage read, by path
+    pub inputs: BTreeMap<String, String>,
+    /// Hash of every file the stage wrote, by path
+    pub outputs: BTreeMap<String, String>,
+    /// Hash of the config section the stage ran with
+    #[serde(default)]
+    pub config: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct PipelineState {
+    pub stages: BTreeMap<String, StageState>,
+}
+
+#[derive(Clone, Copy)]
+enum Stage {
+    DumpId,
+    TemplateSetup,
+    ProveStark,
+    ProveSnark,
+    Verify,
+    TemplateProof,
+}
+
+const STAGES: [Stage; 6] = [
+    Stage::DumpId,
+    Stage::TemplateSetup,
+    Stage::ProveStark,
+    Stage::ProveSnark,
+    Stage::Verify,
+    Stage::TemplateProof,
+];
+
+impl Stage {
+    fn name(&self) -> &'static str {
+        match self {
+            Stage::DumpId => "dump-id",
+            Stage::TemplateSetup => "template-setup",
+            Stage::ProveStark => "prove-stark",
+            Stage::ProveSnark => "prove-snark",
+            Stage::Verify => "verify",
+            Stage::TemplateProof => "template-proof",
+        }
+    }
+
+    fn inputs<'a>(&self, config: &'a PipelineConfig) -> Vec<&'a str> {
+        let outputs = &config.outputs;
+        match self {
+            Stage::DumpId => vec![],
+            Stage::TemplateSetup => vec![&outputs.image_id, &config.templates.constants],
+            Stage::ProveStark => vec![&config.input_file],
+            Stage::ProveSnark => vec![&outputs.receipt],
+            Stage::Verify => vec![&outputs.image_id, &outputs.receipt, &outputs.seal],
+            Stage::TemplateProof => vec![
+                &outputs.image_id,
+                &outputs.receipt,
+                &outputs.seal,
+                &outputs.intermediate,
+            ],
+        }
+    }
+
+    fn outputs<'a>(&self, config: &'a PipelineConfig) -> Vec<&'a str> {
+        let outputs = &config.outputs;
+        match self {
+            Stage::DumpId => vec![&outputs.image_id],
+            Stage::TemplateSetup => vec![&outputs.intermediate],
+            Stage::ProveStark => vec![&outputs.receipt],
+            Stage::ProveSnark => vec![&outputs.seal],
+            Stage::Verify => vec![],
+            Stage::TemplateProof => vec![&outputs.constants],
+        }
+    }
+
+    // The part of the config, other than the file paths, that the result of
+    // the stage depends on
+    fn config(&self, config: &PipelineConfig) -> serde_json::Value {
+        let prover = serde_json::json!({
+            "prover": config.prover.unwrap_or(ProverKind::Local),
+            "r0vm_path": config.r0vm_path,
+        });
+        match self {
+            Stage::ProveStark => serde_json::json!({
+                "prover": prover,
+                "options": config.options.to_json(),
+            }),
+            Stage::ProveSnark => prover,
+            _ => serde_json::Value::Null,
+        }
+    }
+
+    fn run(&self, config: &PipelineConfig) -> Result<(), PipelineError> {
+        let outputs = &config.outputs;
+        let cache = match &config.cache {
+            Some(dir) => Some(ProofCache::open(dir)?),
+            None => None,
+        };
+        let prover = new_prover(
+            config.prover.unwrap_or(ProverKind::Local),
+            config.r0vm_path.as_deref().unwrap_or("r0vm"),
+        );
+        match self {
+            Stage::DumpId => host::dump_image_id(&outputs.image_id)?,
+            Stage::TemplateSetup => {
+                verifier::template_setup(
+                    &get_image_id(&outputs.image_id)?,
+                    &config.templates.constants,
+                    &outputs.intermediate,
+                    false,
+                    false,
+                )?;
+            }
+            Stage::ProveStark => {
+                host::prove_stark(
+                    prover.as_ref(),
+                    read_input(&config.input_file)?,
+                    &config.options,
+                    &outputs.receipt,
+                    cache.as_ref(),
+                )?;
+            }
+            Stage::ProveSnark => {
+                let options = ProveOptions::groth16();
+                let (seal, journal) =
+                    host::prove_snark(prover.as_ref(), &outputs.receipt, &options, cache.as_ref())?;
+                host::save_result(
+                    &ResultType::ProveResult {
+                        seal,
+                        status: "OK".to_string(),
+                        journal: Some(journal),
+                        options: Some(options.to_json()),
+                    },
+                    &outputs.seal,
+                )?;
+            }
+            Stage::Verify => {
+                verifier::verify(
+                    &get_image_id(&outputs.image_id)?,
+                    &get_journal_from_receipt(&outputs.receipt)?,
+                    &get_seal(&outputs.seal)?,
+                )?;
+            }
+            Stage::TemplateProof => {
+                verifier::template_proof(
+                    ProofCheck::Verify(&get_image_id(&outputs.image_id)?),
+                    &get_journal_from_receipt(&outputs.receipt)?,
+                    &get_seal(&outputs.seal)?,
+                    &outputs.intermediate,
+                    &outputs.constants,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read_input(input_file: &str) -> Result<u32, HostError> {
+    let input = std::fs::read_to_string(input_file)
+        .map_err(|_| HostError::Io(format!("Failed to read input file: {}", input_file)))?;
+    input.trim().parse().map_err(|_| {
+        HostError::MalformedInput(format!("Input file {} must contain a u32", input_file))
+    })
+}
+
+fn hash_config(config: &serde_json::Value) -> String {
+    hex::encode(Sha256::digest(config.to_string()))
+}
+
+fn hash_file(name: &str) -> Option<String> {
+    std::fs::read(name)
+        .ok()
+        .map(|data| hex::encode(Sha256::digest(data)))
+}
+
+fn hash_files(names: &[&str]) -> BTreeMap<String, Option<String>> {
+    names
+        .iter()
+        .map(|name| (name.to_string(), hash_file(name)))
+        .collect()
+}
+
+// A stage is up to date when it finished and neither its config, what it read
+// nor what it wrote changed since then
+fn is_up_to_date(state: &StageState, config: &str, inputs: &[&str], outputs: &[&str]) -> bool {
+    let matches = |recorded: &BTreeMap<String, String>, names: &[&str]| {
+        recorded.len() == names.len()
+            && hash_files(names)
+                .iter()
+                .all(|(name, hash)| hash.as_ref() == recorded.get(name))
+    };
+    state.status == StageStatus::Done
+        && state.config == config
+        && matches(&state.inputs, inputs)
+        && matches(&state.outputs, outputs)
+}
+
+fn present_hashes(names: &[&str]) -> BTreeMap<String, String> {
+    hash_files(names)
+        .into_iter()
+        .filter_map(|(name, hash)| hash.map(|hash| (name, hash)))
+        .collect()
+}
+
+pub fn load_config(config_file: &str) -> Result<PipelineConfig, HostError> {
+    let config = std::fs::read_to_string(config_file)
+        .map_err(|_| HostError::Io(format!("Failed to read config file: {}", config_file)))?;
+    toml::from_str(&config).map_err(|e| {
+        HostError::MalformedInput(format!("Invalid config file {}: {}", config_file, e))
+    })
+}
+
+fn load_state(state_file: &str) -> Result<PipelineState, HostError> {
+    match std::fs::read_to_string(state_file) {
+        Ok(state) => serde_json::from_str(&state).map_err(|e| {
+            HostError::MalformedInput(format!("Invalid state file {}: {}", state_file, e))
+        }),
+        Err(_) => Ok(PipelineState::default()),
+    }
+}
+
+fn save_state(state: &PipelineState, state_file: &str) -> Result<(), HostError> {
+    let json = serde_json::to_string_pretty(state)
+        .map_err(|_| HostError::Io("Failed to serialize state".to_string()))?;
+    std::fs::write(state_file, json)
+        .map_err(|_| HostError::Io(format!("Failed to write state file: {}", state_file)))
+}
+
+/// Runs every stage of the pipeline, skipping the ones that are up to date in the
+/// state file. With `restart` the recorded state is ignored.
+pub fn run_pipeline(config_file: &str, restart: bool) -> Result<(), PipelineError> {
+    let config = load_config(config_file)?;
+    if config.guest != host::GUEST_NAME {
+        return Err(HostError::MalformedInput(format!(
+            "Unknown guest {}, only {} is embedded",
+            config.guest,
+            host::GUEST_NAME
+        ))
+        .into());
+    }
+
+    let state_file = config
+        .state_file
+        .clone()
+        .unwrap_or(format!("{}.state.json", config_file));
+    let mut state = match restart {
+        true => PipelineState::default(),
+        false => load_state(&state_file)?,
+    };
+
+    for stage in STAGES {
+        let inputs = stage.inputs(&config);
+        let outputs = stage.outputs(&config);
+        let config_hash = hash_config(&stage.config(&config));
+
+        if let Some(stage_state) = state.stages.get(stage.name()) {
+            if is_up_to_date(stage_state, &config_hash, &inputs, &outputs) {
+                println!("{}: up to date", stage.name());
+                continue;
+            }
+        }
+
+        println!("{}: running", stage.name());
+        let input_hashes = present_hashes(&inputs);
+        let result = stage.run(&config);
+
+        let stage_state = StageState {
+            status: match result {
+                Ok(_) => StageStatus::Done,
+                Err(_) => StageStatus::Failed,
+            },
+            inputs: input_hashes,
+            outputs: present_hashes(&outputs),
+            config: config_hash,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        };
+        state.stages.insert(stage.name().to_string(), stage_state);
+        save_state(&state, &state_file)?;
+
+        if let Err(e) = result {
+            return Err(PipelineError::Stage(stage.name(), Box::new(e)));
+        }
+    }
+
+    println!("Pipeline finished, state saved to {}", state_file);
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use std::process::Command;
+
+use clap::Subcommand;
+
+// The host and verifier commands as bitvmx-zk flattens them
+fn command_set() -> clap::Command {
+    let command = host::cli::Commands::augment_subcommands(clap::Command::new("bitvmx-zk"));
+    verifier::cli::Commands::augment_subcommands(command)
+}
+
+#[test]
+fn command_set_is_consistent() {
+    command_set().debug_assert();
+}
+
+// A short flag names the same argument in every command that takes it
+#[test]
+fn short_flags_are_shared() {
+    let shared = [
+        ('i', "image_id"),
+        ('j', "journal"),
+        ('r', "receipt"),
+        ('s', "seal"),
+    ];
+    for command in command_set().get_subcommands() {
+        for arg in command.get_arguments() {
+            let shorts = arg
+                .get_short()
+                .into_iter()
+                .chain(arg.get_all_short_aliases().unwrap_or_default());
+            for short in shorts {
+                if let Some((_, id)) = shared.iter().find(|(flag, _)| *flag == short) {
+                    assert_eq!(
+                        arg.get_id().as_str(),
+                        *id,
+                        "-{} of {}",
+                        short,
+                        command.get_name()
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn receipt_is_not_taken_with_the_image_id_flag() {
+    let output = Command::new(env!("CARGO_BIN_EXE_bitvmx-zk"))
+        .args(["verify-stark", "-i", "x"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Failed to read receipt"));
+}
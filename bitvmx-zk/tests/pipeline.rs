@@ -0,0 +1,108 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const CONFIG: &str = r#"
+guest = "bitvmx"
+input_file = "input.txt"
+prover = "mock"
+
+[templates]
+constants = "template.h"
+
+[outputs]
+image_id = "image_id.json"
+receipt = "stark-proof.bin"
+seal = "snark-seal.json"
+intermediate = "intermediate.h"
+constants = "constants.h"
+"#;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bitvmx-zk-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn pipeline(dir: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_bitvmx-zk"))
+        .current_dir(dir)
+        .args(["pipeline", "-c", "pipeline.toml"])
+        .output()
+        .unwrap()
+}
+
+fn stage_status(dir: &Path, stage: &str) -> String {
+    let state: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(dir.join("pipeline.toml.state.json")).unwrap(),
+    )
+    .unwrap();
+    state["stages"][stage]["status"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+// The mock seal never verifies, so the pipeline stops at the verify stage. The
+// mock prover executes the guest, which RISC0_SKIP_BUILD leaves empty, so run
+// it with `cargo test -- --ignored`.
+#[test]
+#[ignore = "executes the guest"]
+fn pipeline_resumes_after_a_failed_stage() {
+    assert!(!methods::BITVMX_ELF.is_empty(), "The guest is not built");
+    let dir = temp_dir("pipeline");
+    std::fs::write(dir.join("pipeline.toml"), CONFIG).unwrap();
+    std::fs::write(dir.join("input.txt"), "50\n").unwrap();
+    std::fs::write(dir.join("template.h"), "claim_pre journalx proof_a\n").unwrap();
+
+    let output = pipeline(&dir);
+    assert_eq!(output.status.code(), Some(1));
+    for stage in ["dump-id", "template-setup", "prove-stark", "prove-snark"] {
+        assert_eq!(stage_status(&dir, stage), "done");
+    }
+    assert_eq!(stage_status(&dir, "verify"), "failed");
+
+    let output = pipeline(&dir);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("prove-stark: up to date"));
+    assert!(stdout.contains("prove-snark: up to date"));
+    assert!(stdout.contains("verify: running"));
+
+    // A new input invalidates the proving stages
+    std::fs::write(dir.join("input.txt"), "150\n").unwrap();
+    let stdout = String::from_utf8_lossy(&pipeline(&dir).stdout).to_string();
+    assert!(stdout.contains("template-setup: up to date"));
+    assert!(stdout.contains("prove-stark: running"));
+    assert!(stdout.contains("prove-snark: running"));
+
+    // So do new prover options, only for the stark
+    let options = CONFIG.replace(
+        "[templates]",
+        "[options]\nsegment_limit_po2 = 18\n\n[templates]",
+    );
+    std::fs::write(dir.join("pipeline.toml"), options).unwrap();
+    let stdout = String::from_utf8_lossy(&pipeline(&dir).stdout).to_string();
+    assert!(stdout.contains("prove-stark: running"));
+    assert!(stdout.contains("prove-snark: up to date"));
+}
+
+#[test]
+fn pipeline_errors_keep_their_exit_code() {
+    let dir = temp_dir("pipeline-errors");
+
+    // A missing config is an I/O error and an invalid one malformed input
+    assert_eq!(pipeline(&dir).status.code(), Some(74));
+    std::fs::write(dir.join("pipeline.toml"), "guest = ").unwrap();
+    assert_eq!(pipeline(&dir).status.code(), Some(65));
+    let unknown = CONFIG.replace(r#"guest = "bitvmx""#, r#"guest = "other""#);
+    std::fs::write(dir.join("pipeline.toml"), unknown).unwrap();
+    assert_eq!(pipeline(&dir).status.code(), Some(65));
+
+    // The template of the setup stage is missing
+    std::fs::write(dir.join("pipeline.toml"), CONFIG).unwrap();
+    let output = pipeline(&dir);
+    assert_eq!(output.status.code(), Some(74));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Stage template-setup failed"));
+    assert_eq!(stage_status(&dir, "template-setup"), "failed");
+}
@@ -0,0 +1,199 @@
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use methods::BITVMX_ID;
+use risc0_zkvm::{InnerReceipt, Receipt};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::HostError;
+
+const ENTRY_FILE: &str = "entry.json";
+const RECEIPT_FILE: &str = "receipt.bin";
+
+/// Metadata of a cached receipt. The key is the hash of every other field but the
+/// timestamps, so the same request always maps to the same entry.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CacheEntry {
+    pub key: String,
+    /// `stark`, `snark` or `compress`
+    pub kind: String,
+    pub image_id: String,
+    pub input_hash: String,
+    pub options: String,
+    pub created: u64,
+    pub last_used: u64,
+}
+
+impl CacheEntry {
+    pub fn new(kind: &str, input: &[u8], options: &str) -> Self {
+        let image_id = hex::encode(
+            BITVMX_ID
+                .iter()
+                .flat_map(|word| word.to_le_bytes())
+                .collect::<Vec<u8>>(),
+        );
+        let input_hash = hex::encode(Sha256::digest(input));
+
+        let mut hasher = Sha256::new();
+        for field in [kind, &image_id, &input_hash, options] {
+            hasher.update((field.len() as u32).to_le_bytes());
+            hasher.update(field.as_bytes());
+        }
+
+        CacheEntry {
+            key: hex::encode(hasher.finalize()),
+            kind: kind.to_string(),
+            image_id,
+            input_hash,
+            options: options.to_string(),
+            created: 0,
+            last_used: 0,
+        }
+    }
+}
+
+/// On-disk cache of receipts, one directory per entry
+pub struct ProofCache {
+    dir: PathBuf,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl ProofCache {
+    pub fn open(dir: &str) -> Result<Self, HostError> {
+        std::fs::create_dir_all(dir)
+            .map_err(|_| HostError::Io(format!("Failed to create cache directory: {}", dir)))?;
+        Ok(ProofCache {
+            dir: PathBuf::from(dir),
+        })
+    }
+
+    fn entry_dir(&self, key: &str) -> PathBuf {
+        self.dir.join(key)
+    }
+
+    fn write_entry(&self, entry: &CacheEntry) -> Result<(), HostError> {
+        let json = serde_json::to_string_pretty(entry)
+            .map_err(|_| HostError::Io("Failed to serialize cache entry".to_string()))?;
+        std::fs::write(self.entry_dir(&entry.key).join(ENTRY_FILE), json)
+            .map_err(|_| HostError::Io(format!("Failed to write cache entry: {}", entry.key)))
+    }
+
+    fn read_receipt(&self, key: &str) -> Result<Receipt, HostError> {
+        let data = std::fs::read(self.entry_dir(key).join(RECEIPT_FILE))
+            .map_err(|_| HostError::Io(format!("Failed to read cached receipt: {}", key)))?;
+        bincode::deserialize(&data)
+            .map_err(|_| HostError::MalformedInput(format!("Malformed cached receipt: {}", key)))
+    }
+
+    fn remove(&self, key: &str) -> Result<(), HostError> {
+        std::fs::remove_dir_all(self.entry_dir(key))
+            .map_err(|_| HostError::Io(format!("Failed to remove cache entry: {}", key)))
+    }
+
+    /// Returns the cached receipt once it verifies against the image id. An entry
+    /// that does not verify is dropped from the cache.
+    pub fn get(&self, entry: &CacheEntry) -> Option<Receipt> {
+        let mut stored = self.load_entry(&entry.key)?;
+
+        let receipt = match self.verify_entry(&entry.key) {
+            Ok(receipt) => receipt,
+            Err(e) => {
+                eprintln!("Dropping cache entry {}: {}", entry.key, e);
+                let _ = self.remove(&entry.key);
+                return None;
+            }
+        };
+
+        stored.last_used = now();
+        let _ = self.write_entry(&stored);
+        eprintln!("Using cached {} receipt: {}", entry.kind, entry.key);
+        Some(receipt)
+    }
+
+    /// Stores the receipt of the entry. Fake receipts, made by the mock prover
+    /// or in dev mode, are never stored: the key does not name the prover, and
+    /// in dev mode they would verify for a request to any of them.
+    pub fn put(&self, entry: &CacheEntry, receipt: &Receipt) -> Result<(), HostError> {
+        if matches!(receipt.inner, InnerReceipt::Fake(_)) {
+            return Ok(());
+        }
+        let dir = self.entry_dir(&entry.key);
+        std::fs::create_dir_all(&dir)
+            .map_err(|_| HostError::Io(format!("Failed to create cache entry: {}", entry.key)))?;
+
+        let data = bincode::serialize(receipt)
+            .map_err(|_| HostError::Io("Failed to serialize receipt".to_string()))?;
+        std::fs::write(dir.join(RECEIPT_FILE), data)
+            .map_err(|_| HostError::Io(format!("Failed to write cached receipt: {}", entry.key)))?;
+
+        let time = now();
+        self.write_entry(&CacheEntry {
+            created: time,
+            last_used: time,
+            ..entry.clone()
+        })
+    }
+
+    fn load_entry(&self, key: &str) -> Option<CacheEntry> {
+        let json = std::fs::read_to_string(self.entry_dir(key).join(ENTRY_FILE)).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    fn verify_entry(&self, key: &str) -> Result<Receipt, HostError> {
+        let receipt = self.read_receipt(key)?;
+        if matches!(receipt.inner, InnerReceipt::Fake(_)) {
+            return Err(HostError::InvalidProof(
+                "Cached receipt is a fake receipt".to_string(),
+            ));
+        }
+        receipt.verify(BITVMX_ID).map_err(|e| {
+            HostError::InvalidProof(format!("Cached receipt does not verify: {}", e))
+        })?;
+        Ok(receipt)
+    }
+
+    /// Every entry in the cache, least recently used first
+    pub fn list(&self) -> Result<Vec<CacheEntry>, HostError> {
+        let dir = std::fs::read_dir(&self.dir).map_err(|_| {
+            HostError::Io(format!(
+                "Failed to read cache directory: {}",
+                self.dir.display()
+            ))
+        })?;
+
+        let mut entries: Vec<CacheEntry> = dir
+            .filter_map(|item| item.ok())
+            .filter_map(|item| self.load_entry(&item.file_name().to_string_lossy()))
+            .collect();
+        entries.sort_by_key(|entry| entry.last_used);
+        Ok(entries)
+    }
+
+    /// Removes the entries not used in the last `max_age` seconds, or every entry
+    /// when there is no age. With `invalid` the entries that do not verify are
+    /// removed as well. Returns the removed entries.
+    pub fn prune(&self, max_age: Option<u64>, invalid: bool) -> Result<Vec<CacheEntry>, HostError> {
+        let time = now();
+        let mut removed = Vec::new();
+
+        for entry in self.list()? {
+            let expired = match max_age {
+                Some(max_age) => time.saturating_sub(entry.last_used) > max_age,
+                None => !invalid,
+            };
+            if expired || (invalid && self.verify_entry(&entry.key).is_err()) {
+                self.remove(&entry.key)?;
+                removed.push(entry);
+            }
+        }
+
+        Ok(removed)
+    }
+}
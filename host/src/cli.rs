@@ -0,0 +1,389 @@
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use tracing_subscriber::EnvFilter;
+use zk_result::ResultType;
+
+use crate::cache::ProofCache;
+use crate::error::HostError;
+use crate::prover::{new_prover, ProveOptions, ProverKind, ReceiptKind, ZkProver};
+use crate::witness::{export_witness, import_groth16};
+use crate::{
+    compress, dump_image_id, image_id_json, prove_snark, prove_stark, save_result, verify_stark,
+};
+
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+// Backend used to prove, see the prover module
+#[derive(Args)]
+pub struct ProverArgs {
+    /// Prover backend
+    #[arg(long, value_enum, default_value_t = ProverKind::Local)]
+    prover: ProverKind,
+
+    /// Path of the r0vm binary used by the r0vm prover
+    #[arg(long, value_name = "PATH", default_value = "r0vm")]
+    r0vm_path: String,
+}
+
+impl ProverArgs {
+    fn get(&self) -> Box<dyn ZkProver> {
+        new_prover(self.prover, &self.r0vm_path)
+    }
+}
+
+// Options the stark is proven with, recorded in the result file
+#[derive(Args)]
+pub struct ProveOptionsArgs {
+    /// Kind of receipt to generate
+    #[arg(long, value_enum, default_value_t = ReceiptKind::Composite)]
+    receipt_kind: ReceiptKind,
+
+    /// Maximum segment size as a power of two cycles
+    #[arg(long, value_name = "PO2")]
+    segment_limit_po2: Option<u32>,
+
+    /// Maximum number of cycles of the whole execution
+    #[arg(long, value_name = "CYCLES")]
+    session_cycle_limit: Option<u64>,
+}
+
+impl ProveOptionsArgs {
+    fn get(&self) -> ProveOptions {
+        ProveOptions {
+            receipt_kind: self.receipt_kind,
+            segment_limit_po2: self.segment_limit_po2,
+            session_cycle_limit: self.session_cycle_limit,
+            ..Default::default()
+        }
+    }
+}
+
+// Argument names are shared with the verifier, as both make up the bitvmx-zk
+// command set: -r is always a receipt file, -i an image id file and -s a seal
+// result file.
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Generate the stark proof
+    ProveStark {
+        /// Input that proves the stark
+        #[arg(long)]
+        input: u32,
+
+        /// Output receipt file
+        #[arg(short, long, value_name = "FILE")]
+        receipt: String,
+
+        /// Output JSON result file
+        #[arg(long, value_name = "JSON_FILE")]
+        result: Option<String>,
+
+        /// Proof cache directory, a cached receipt for the same input is reused
+        #[arg(long, value_name = "DIR")]
+        cache: Option<String>,
+
+        #[command(flatten)]
+        prover: ProverArgs,
+
+        #[command(flatten)]
+        options: ProveOptionsArgs,
+    },
+
+    /// Verify the stark proof
+    VerifyStark {
+        /// Stark receipt file
+        #[arg(short, long, value_name = "FILE")]
+        receipt: String,
+    },
+
+    /// Convert a stark proof to a groth16 proof
+    ProveSnark {
+        /// Stark receipt file
+        #[arg(short, long, value_name = "FILE")]
+        receipt: String,
+
+        /// Output JSON result file with the groth16 seal
+        #[arg(short, long, value_name = "JSON_FILE")]
+        seal: String,
+
+        /// JSON result file that must have an OK status before proving
+        #[arg(long, value_name = "JSON_FILE")]
+        check_result: Option<String>,
+
+        /// Proof cache directory, a cached seal for the same receipt is reused
+        #[arg(long, value_name = "DIR")]
+        cache: Option<String>,
+
+        #[command(flatten)]
+        prover: ProverArgs,
+    },
+
+    /// Compress a receipt one step: composite to succinct or succinct to groth16
+    Compress {
+        /// Receipt file to compress
+        #[arg(short, long, value_name = "FILE")]
+        receipt: String,
+
+        /// Output file of the compressed receipt, written once it verifies
+        #[arg(short, long, value_name = "FILE")]
+        output: String,
+
+        /// Kind of the compressed receipt, succinct or groth16
+        #[arg(long, value_enum)]
+        to: ReceiptKind,
+
+        /// Proof cache directory, a cached compression of the same receipt is reused
+        #[arg(long, value_name = "DIR")]
+        cache: Option<String>,
+
+        #[command(flatten)]
+        prover: ProverArgs,
+    },
+
+    /// Export the identity_p254 receipt and the groth16 witness of a succinct receipt
+    ExportWitness {
+        /// Succinct receipt file
+        #[arg(short, long, value_name = "FILE")]
+        receipt: String,
+
+        /// Output file of the identity_p254 receipt
+        #[arg(long, value_name = "FILE")]
+        p254: String,
+
+        /// Output file of the witness, the input.json of the groth16 prover
+        #[arg(short, long, value_name = "JSON_FILE")]
+        witness: String,
+    },
+
+    /// Import a groth16 proof made by an external prover as a seal result file
+    ImportSnark {
+        /// Succinct or identity_p254 receipt the proof was generated for
+        #[arg(short, long, value_name = "FILE")]
+        receipt: String,
+
+        /// Proof file in the snarkjs proof.json format, also written by rapidsnark
+        #[arg(short, long, value_name = "JSON_FILE")]
+        proof: String,
+
+        /// Output JSON result file with the groth16 seal
+        #[arg(short, long, value_name = "JSON_FILE")]
+        seal: String,
+    },
+
+    /// Dump the ELF_ID that will be used as part of the groth proof
+    DumpId {
+        /// ID file
+        #[arg(short, long, value_name = "FILE")]
+        image_id: String,
+    },
+
+    /// Inspect or clean the proof cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommands {
+    /// List the cached receipts, least recently used first
+    List {
+        /// Proof cache directory
+        #[arg(long, value_name = "DIR")]
+        cache: String,
+    },
+
+    /// Remove cached receipts
+    #[command(group(ArgGroup::new("filter").required(true).multiple(true)))]
+    Prune {
+        /// Proof cache directory
+        #[arg(long, value_name = "DIR")]
+        cache: String,
+
+        /// Remove the entries not used in the last DAYS days
+        #[arg(long, value_name = "DAYS", group = "filter")]
+        older_than: Option<u64>,
+
+        /// Remove the entries whose receipt does not verify
+        #[arg(long, group = "filter")]
+        invalid: bool,
+
+        /// Remove every entry
+        #[arg(long, group = "filter", conflicts_with_all = ["older_than", "invalid"])]
+        all: bool,
+    },
+}
+
+pub fn init_logging() {
+    // Logs go to stderr, stdout carries the results
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+pub fn run() -> Result<(), HostError> {
+    init_logging();
+
+    let cli = Cli::parse();
+
+    match &cli.command {
+        Some(command) => execute(command),
+        None => {
+            println!("No command provided");
+            Ok(())
+        }
+    }
+}
+
+// A failed proof is also recorded in the result file, with the error as status
+fn prove_result(
+    result: &Result<(Vec<u8>, Vec<u8>), HostError>,
+    options: &ProveOptions,
+) -> ResultType {
+    match result {
+        Ok((seal, journal)) => ResultType::ProveResult {
+            seal: seal.clone(),
+            status: "OK".to_string(),
+            journal: Some(journal.clone()),
+            options: Some(options.to_json()),
+        },
+        Err(e) => ResultType::ProveResult {
+            seal: Vec::new(),
+            status: e.to_string(),
+            journal: None,
+            options: Some(options.to_json()),
+        },
+    }
+}
+
+pub fn execute(command: &Commands) -> Result<(), HostError> {
+    match command {
+        Commands::ProveStark {
+            input,
+            receipt,
+            result: json,
+            cache,
+            prover,
+            options,
+        } => {
+            let cache = open_cache(cache)?;
+            let options = options.get();
+            let result = prove_stark(
+                prover.get().as_ref(),
+                *input,
+                &options,
+                receipt,
+                cache.as_ref(),
+            )
+            .map(|journal| (Vec::new(), journal));
+            if let Some(json) = json {
+                save_result(&prove_result(&result, &options), json)?;
+            }
+            result?;
+        }
+        Commands::VerifyStark { receipt } => verify_stark(receipt)?,
+        Commands::ProveSnark {
+            receipt,
+            seal,
+            check_result,
+            cache,
+            prover,
+        } => {
+            let options = ProveOptions::groth16();
+            if let Some(input_json_file) = check_result {
+                validate_json_status(input_json_file)?;
+            }
+
+            let cache = open_cache(cache)?;
+            let result = prove_snark(prover.get().as_ref(), receipt, &options, cache.as_ref());
+            save_result(&prove_result(&result, &options), seal)?;
+            result?;
+        }
+        Commands::Compress {
+            receipt,
+            output,
+            to,
+            cache,
+            prover,
+        } => {
+            let options = ProveOptions {
+                receipt_kind: *to,
+                ..Default::default()
+            };
+            let cache = open_cache(cache)?;
+            compress(
+                prover.get().as_ref(),
+                receipt,
+                &options,
+                output,
+                cache.as_ref(),
+            )?
+        }
+        Commands::ExportWitness {
+            receipt,
+            p254,
+            witness,
+        } => export_witness(receipt, p254, witness)?,
+        Commands::ImportSnark {
+            receipt,
+            proof,
+            seal,
+        } => import_groth16(receipt, proof, seal)?,
+        Commands::DumpId { image_id } => {
+            println!("ID: {}", image_id_json().pretty(2));
+            dump_image_id(image_id)?;
+        }
+        Commands::Cache { command } => execute_cache(command)?,
+    };
+    Ok(())
+}
+
+fn execute_cache(command: &CacheCommands) -> Result<(), HostError> {
+    match command {
+        CacheCommands::List { cache } => {
+            for entry in ProofCache::open(cache)?.list()? {
+                println!(
+                    "{} {} {} input:{} last_used:{}",
+                    entry.key, entry.kind, entry.options, entry.input_hash, entry.last_used
+                );
+            }
+        }
+        CacheCommands::Prune {
+            cache,
+            older_than,
+            invalid,
+            all: _,
+        } => {
+            let max_age = older_than.map(|days| days * 24 * 60 * 60);
+            let removed = ProofCache::open(cache)?.prune(max_age, *invalid)?;
+            for entry in &removed {
+                println!("Removed {} {}", entry.key, entry.kind);
+            }
+            println!("{} entries removed", removed.len());
+        }
+    }
+    Ok(())
+}
+
+fn open_cache(cache: &Option<String>) -> Result<Option<ProofCache>, HostError> {
+    cache.as_deref().map(ProofCache::open).transpose()
+}
+
+// The result of the previous step must be OK to go on
+fn validate_json_status(json: &str) -> Result<(), HostError> {
+    let json_content = std::fs::read_to_string(json)
+        .map_err(|_| HostError::Io(format!("Failed to read result file: {}", json)))?;
+    let result = ResultType::from_json_string(json_content).map_err(HostError::MalformedInput)?;
+
+    match result.get_status().as_str() {
+        "OK" => Ok(()),
+        status => Err(HostError::MalformedInput(format!(
+            "Status of {} is not OK: {}",
+            json, status
+        ))),
+    }
+}
@@ -0,0 +1,36 @@
+use std::fmt;
+
+use zk_result::{EXIT_INVALID_PROOF, EXIT_IO, EXIT_MALFORMED_INPUT, EXIT_PROVER};
+
+#[derive(Debug)]
+pub enum HostError {
+    InvalidProof(String),
+    MalformedInput(String),
+    Prover(String),
+    Io(String),
+}
+
+impl HostError {
+    /// Process exit code of the error, the same as the verifier uses
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            HostError::InvalidProof(_) => EXIT_INVALID_PROOF,
+            HostError::MalformedInput(_) => EXIT_MALFORMED_INPUT,
+            HostError::Prover(_) => EXIT_PROVER,
+            HostError::Io(_) => EXIT_IO,
+        }
+    }
+}
+
+impl fmt::Display for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostError::InvalidProof(e) => write!(f, "Invalid proof: {}", e),
+            HostError::MalformedInput(e) => write!(f, "Malformed input: {}", e),
+            HostError::Prover(e) => write!(f, "Prover error: {}", e),
+            HostError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for HostError {}
@@ -1,47 +1,90 @@
-// These constants represent the RISC-V ELF and the image ID generated by risc0-build.
-// The ELF is used for proving and the ID is used for verification.
-use methods::{BITVMX_ELF, BITVMX_ID};
-use risc0_zkvm::{default_prover, get_prover_server, ExecutorEnv, ProverOpts, Receipt};
+pub mod cache;
+pub mod cli;
+pub mod error;
+pub mod prover;
+pub mod witness;
 
-pub fn serialize_receipt(receipt: Receipt, name: &str) -> Result<(), String> {
+use json::JsonValue;
+// The image ID generated by risc0-build is used for verification, the ELF is
+// proved by the prover backends.
+use methods::BITVMX_ID;
+use risc0_zkvm::{InnerReceipt, Receipt};
+use zk_result::ResultType;
+
+use cache::{CacheEntry, ProofCache};
+use error::HostError;
+use prover::{ProveOptions, ReceiptKind, ZkProver};
+
+/// Name of the guest package embedded by the methods crate
+pub const GUEST_NAME: &str = "bitvmx";
+
+pub fn serialize_receipt(receipt: Receipt, name: &str) -> Result<(), HostError> {
     //serialize with bincode
-    let receipt_bytes = bincode::serialize(&receipt).map_err(|_| "Failed to serialize receipt")?;
+    let receipt_bytes = bincode::serialize(&receipt)
+        .map_err(|_| HostError::Io("Failed to serialize receipt".to_string()))?;
     //save to file
     let path = std::path::Path::new(name);
     std::fs::write(path, receipt_bytes)
-        .map_err(|_| format!("Failed to write receipt to file: {}", name))?;
+        .map_err(|_| HostError::Io(format!("Failed to write receipt to file: {}", name)))?;
 
     Ok(())
 }
 
-pub fn deserialize_receipt(name: &str) -> Receipt {
-    //deserialize receipt from file using bin code
-    let path = std::path::Path::new(name);
-    let receipt_bytes = std::fs::read(path).unwrap();
-    let receipt: Receipt = bincode::deserialize(&receipt_bytes).unwrap();
-    receipt
+pub fn read_receipt(name: &str) -> Result<Receipt, HostError> {
+    let data = std::fs::read(name)
+        .map_err(|_| HostError::Io(format!("Failed to read receipt file: {}", name)))?;
+    bincode::deserialize(&data).map_err(|_| {
+        HostError::MalformedInput(format!("Failed to deserialize receipt file: {}", name))
+    })
+}
+
+pub fn save_result(result: &ResultType, name: &str) -> Result<(), HostError> {
+    let json = serde_json::to_string(result)
+        .map_err(|_| HostError::Io("Failed to serialize result to JSON".to_string()))?;
+    std::fs::write(name, json)
+        .map_err(|_| HostError::Io(format!("Failed to write result to file: {}", name)))
 }
 
-pub fn prove_stark(input: u32, output_file: &str) -> Result<(), String> {
-    // Initialize the executor environment with the input.
-    let env = ExecutorEnv::builder()
-        .write(&input)
-        .map_err(|e| format!("Failed to write input: {}", e))?
-        .build()
-        .map_err(|e| format!("Failed to build environment: {}", e))?;
+pub fn image_id_json() -> JsonValue {
+    let mut json = JsonValue::new_array();
+    for value in BITVMX_ID.iter() {
+        let _ = json.push(*value);
+    }
+    json
+}
 
-    // Obtain the default prover.
-    let prover = default_prover();
+pub fn dump_image_id(output_file: &str) -> Result<(), HostError> {
+    std::fs::write(output_file, image_id_json().dump())
+        .map_err(|_| HostError::Io(format!("Failed to write image id to file: {}", output_file)))
+}
 
-    // Proof information by proving the specified ELF binary.
-    // This struct contains the receipt along with statistics about execution of the guest
-    let prove_info = prover
-        .prove(env, BITVMX_ELF)
-        .map_err(|e| format!("Failed to prove: {}", e))?;
+pub fn prove_stark(
+    prover: &dyn ZkProver,
+    input: u32,
+    options: &ProveOptions,
+    output_file: &str,
+    cache: Option<&ProofCache>,
+) -> Result<Vec<u8>, HostError> {
+    let entry = CacheEntry::new(
+        "stark",
+        &input.to_le_bytes(),
+        &options.to_json().to_string(),
+    );
+    if let Some(receipt) = cache.and_then(|cache| cache.get(&entry)) {
+        let journal = receipt.journal.bytes.clone();
+        serialize_receipt(receipt, output_file)?;
+        println!("The cached receipt was saved to the file: {}", output_file);
+        return Ok(journal);
+    }
 
-    // extract the receipt.
-    let receipt = prove_info.receipt;
+    let receipt = prover
+        .prove_stark(input, options)
+        .map_err(HostError::Prover)?;
+    let journal = receipt.journal.bytes.clone();
 
+    if let Some(cache) = cache {
+        cache.put(&entry, &receipt)?;
+    }
     serialize_receipt(receipt, output_file)?;
 
     println!(
@@ -49,33 +92,133 @@ pub fn prove_stark(input: u32, output_file: &str) -> Result<(), String> {
         output_file
     );
 
-    Ok(())
+    Ok(journal)
 }
 
-pub fn verify_stark(receipt_fname: &str) {
-    let receipt = deserialize_receipt(receipt_fname);
-    receipt.verify(BITVMX_ID).unwrap();
+pub fn verify_stark(receipt_fname: &str) -> Result<(), HostError> {
+    let receipt = read_receipt(receipt_fname)?;
+    receipt
+        .verify(BITVMX_ID)
+        .map_err(|e| HostError::InvalidProof(format!("The receipt does not verify: {}", e)))?;
     println!("Receipt verified successfully");
+    Ok(())
 }
 
-pub fn prove_snark(receipt_name: &str) -> Result<Vec<u8>, String> {
-    let receipt = deserialize_receipt(receipt_name);
+// Returns the groth16 seal together with the journal it proves
+pub fn prove_snark(
+    prover: &dyn ZkProver,
+    receipt_name: &str,
+    options: &ProveOptions,
+    cache: Option<&ProofCache>,
+) -> Result<(Vec<u8>, Vec<u8>), HostError> {
+    if options.receipt_kind != ReceiptKind::Groth16 {
+        return Err(HostError::MalformedInput(
+            "The snark proof is a groth16 receipt".to_string(),
+        ));
+    }
+    let receipt_data = std::fs::read(receipt_name)
+        .map_err(|_| HostError::Io(format!("Failed to read receipt file: {}", receipt_name)))?;
+    let entry = CacheEntry::new("snark", &receipt_data, &options.to_json().to_string());
 
-    let groth16_receipt = get_prover_server(&ProverOpts::groth16())
-        .map_err(|e| format!("Failed to get prover server: {}", e))?
-        .compress(&ProverOpts::groth16(), &receipt)
-        .map_err(|e| format!("Failed to compress receipt: {}", e))?;
+    let groth16_receipt = match cache.and_then(|cache| cache.get(&entry)) {
+        Some(receipt) => receipt,
+        None => {
+            let receipt = read_receipt(receipt_name)?;
+            let groth16_receipt = prover
+                .compress(&receipt, options)
+                .map_err(HostError::Prover)?;
+            if let Some(cache) = cache {
+                cache.put(&entry, &groth16_receipt)?;
+            }
+            groth16_receipt
+        }
+    };
 
     let mut data_vec = Vec::new();
     for data in &groth16_receipt
         .inner
         .groth16()
-        .map_err(|e| format!("Failed to get groth16 data: {}", e))?
+        .map_err(|e| HostError::Prover(format!("Failed to get groth16 data: {}", e)))?
         .seal
     {
         data_vec.push(*data);
     }
 
-    println!("The proof was executed, and the seal saved in the receipt: {}", receipt_name);
-    Ok(data_vec)
+    println!(
+        "The proof was executed, and the seal saved in the receipt: {}",
+        receipt_name
+    );
+    Ok((data_vec, groth16_receipt.journal.bytes))
+}
+
+fn receipt_kind(receipt: &Receipt) -> &'static str {
+    match receipt.inner {
+        InnerReceipt::Composite(_) => ReceiptKind::Composite.name(),
+        InnerReceipt::Succinct(_) => ReceiptKind::Succinct.name(),
+        InnerReceipt::Groth16(_) => ReceiptKind::Groth16.name(),
+        InnerReceipt::Fake(_) => "fake",
+        _ => "unknown",
+    }
+}
+
+// Runs a single compression step, composite to succinct or succinct to
+// groth16, and writes the compressed receipt once it verifies. Fake receipts
+// of the mock prover are accepted at every step.
+pub fn compress(
+    prover: &dyn ZkProver,
+    receipt_name: &str,
+    options: &ProveOptions,
+    output_file: &str,
+    cache: Option<&ProofCache>,
+) -> Result<(), HostError> {
+    let receipt_data = std::fs::read(receipt_name)
+        .map_err(|_| HostError::Io(format!("Failed to read receipt file: {}", receipt_name)))?;
+    let receipt: Receipt = bincode::deserialize(&receipt_data).map_err(|_| {
+        HostError::MalformedInput(format!(
+            "Failed to deserialize receipt file: {}",
+            receipt_name
+        ))
+    })?;
+
+    let expected = match options.receipt_kind {
+        ReceiptKind::Composite => {
+            return Err(HostError::MalformedInput(
+                "Receipts cannot be compressed to composite".into(),
+            ))
+        }
+        ReceiptKind::Succinct => ReceiptKind::Composite.name(),
+        ReceiptKind::Groth16 => ReceiptKind::Succinct.name(),
+    };
+    let kind = receipt_kind(&receipt);
+    if kind != expected && kind != "fake" {
+        return Err(HostError::MalformedInput(format!(
+            "Expected a {} receipt but {} is {}",
+            expected, receipt_name, kind
+        )));
+    }
+
+    let entry = CacheEntry::new("compress", &receipt_data, &options.to_json().to_string());
+    let compressed = match cache.and_then(|cache| cache.get(&entry)) {
+        Some(compressed) => compressed,
+        None => {
+            let compressed = prover
+                .compress(&receipt, options)
+                .map_err(HostError::Prover)?;
+            compressed.verify(BITVMX_ID).map_err(|e| {
+                HostError::InvalidProof(format!("The compressed receipt does not verify: {}", e))
+            })?;
+            if let Some(cache) = cache {
+                cache.put(&entry, &compressed)?;
+            }
+            compressed
+        }
+    };
+    serialize_receipt(compressed, output_file)?;
+
+    println!(
+        "The {} receipt was verified and saved to the file: {}",
+        options.receipt_kind.name(),
+        output_file
+    );
+    Ok(())
 }
@@ -0,0 +1,265 @@
+use clap::ValueEnum;
+use methods::BITVMX_ELF;
+use risc0_zkvm::sha::Digestible;
+use risc0_zkvm::{
+    default_executor, ExecutorEnv, FakeReceipt, Groth16Receipt, Groth16ReceiptVerifierParameters,
+    InnerReceipt, Prover, ProverOpts, Receipt,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ReceiptKind {
+    /// One receipt per segment
+    Composite,
+    /// A single recursion receipt
+    Succinct,
+    /// A groth16 receipt, needs docker
+    Groth16,
+}
+
+impl ReceiptKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReceiptKind::Composite => "composite",
+            ReceiptKind::Succinct => "succinct",
+            ReceiptKind::Groth16 => "groth16",
+        }
+    }
+}
+
+/// Hash function of the stark proving protocol. risc0-zkvm 2 only proves
+/// with poseidon2, in the segment proofs as in the recursion circuit.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum HashFn {
+    #[serde(rename = "poseidon2")]
+    Poseidon2,
+}
+
+impl HashFn {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashFn::Poseidon2 => "poseidon2",
+        }
+    }
+}
+
+/// Options the guest is proven with. They are recorded in the result files, as
+/// the segment size dominates the memory used by the prover.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ProveOptions {
+    pub receipt_kind: ReceiptKind,
+    pub hash_fn: HashFn,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segment_limit_po2: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_cycle_limit: Option<u64>,
+}
+
+impl Default for ProveOptions {
+    fn default() -> Self {
+        ProveOptions {
+            receipt_kind: ReceiptKind::Composite,
+            hash_fn: HashFn::Poseidon2,
+            segment_limit_po2: None,
+            session_cycle_limit: None,
+        }
+    }
+}
+
+impl ProveOptions {
+    /// Options of the groth16 compression
+    pub fn groth16() -> Self {
+        ProveOptions {
+            receipt_kind: ReceiptKind::Groth16,
+            ..Default::default()
+        }
+    }
+
+    pub fn prover_opts(&self) -> ProverOpts {
+        let opts = match self.receipt_kind {
+            ReceiptKind::Composite => ProverOpts::composite(),
+            ReceiptKind::Succinct => ProverOpts::succinct(),
+            ReceiptKind::Groth16 => ProverOpts::groth16(),
+        };
+        opts.with_hashfn(self.hash_fn.name().to_string())
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("prove options are always serializable")
+    }
+}
+
+/// Backend that proves the guest and compresses its receipts
+pub trait ZkProver {
+    /// Proves the guest for the given input and returns a receipt of the requested kind
+    fn prove_stark(&self, input: u32, options: &ProveOptions) -> Result<Receipt, String>;
+
+    /// Compresses a receipt into the requested kind
+    fn compress(&self, receipt: &Receipt, options: &ProveOptions) -> Result<Receipt, String>;
+}
+
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ProverKind {
+    /// Prove in this process
+    Local,
+    /// Prove in an external r0vm process
+    R0vm,
+    /// Fake receipts, for tests only
+    Mock,
+}
+
+pub fn new_prover(kind: ProverKind, r0vm_path: &str) -> Box<dyn ZkProver> {
+    match kind {
+        ProverKind::Local => Box::new(LocalProver),
+        ProverKind::R0vm => Box::new(ExternalProver {
+            r0vm_path: r0vm_path.to_string(),
+        }),
+        ProverKind::Mock => Box::new(MockProver),
+    }
+}
+
+// Executor environment with the input and the execution limits
+fn executor_env(input: u32, options: &ProveOptions) -> Result<ExecutorEnv<'static>, String> {
+    let mut builder = ExecutorEnv::builder();
+    builder
+        .write(&input)
+        .map_err(|e| format!("Failed to write input: {}", e))?
+        .session_limit(options.session_cycle_limit);
+    if let Some(po2) = options.segment_limit_po2 {
+        builder.segment_limit_po2(po2);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Failed to build environment: {}", e))
+}
+
+fn prove_with(prover: &dyn Prover, input: u32, options: &ProveOptions) -> Result<Receipt, String> {
+    let env = executor_env(input, options)?;
+
+    // Proof information by proving the specified ELF binary.
+    // This struct contains the receipt along with statistics about execution of the guest
+    let prove_info = prover
+        .prove_with_opts(env, BITVMX_ELF, &options.prover_opts())
+        .map_err(|e| format!("Failed to prove: {}", e))?;
+
+    Ok(prove_info.receipt)
+}
+
+fn compress_with(
+    prover: &dyn Prover,
+    receipt: &Receipt,
+    options: &ProveOptions,
+) -> Result<Receipt, String> {
+    prover
+        .compress(&options.prover_opts(), receipt)
+        .map_err(|e| format!("Failed to compress receipt: {}", e))
+}
+
+/// Proves in this process, the groth16 step needs docker
+pub struct LocalProver;
+
+impl ZkProver for LocalProver {
+    fn prove_stark(&self, input: u32, options: &ProveOptions) -> Result<Receipt, String> {
+        prove_with(&risc0_zkvm::LocalProver::new("local"), input, options)
+    }
+
+    fn compress(&self, receipt: &Receipt, options: &ProveOptions) -> Result<Receipt, String> {
+        compress_with(&risc0_zkvm::LocalProver::new("local"), receipt, options)
+    }
+}
+
+/// Proves in an `r0vm` process started from the given path
+pub struct ExternalProver {
+    pub r0vm_path: String,
+}
+
+impl ZkProver for ExternalProver {
+    fn prove_stark(&self, input: u32, options: &ProveOptions) -> Result<Receipt, String> {
+        prove_with(
+            &risc0_zkvm::ExternalProver::new("r0vm", &self.r0vm_path),
+            input,
+            options,
+        )
+    }
+
+    fn compress(&self, receipt: &Receipt, options: &ProveOptions) -> Result<Receipt, String> {
+        compress_with(
+            &risc0_zkvm::ExternalProver::new("r0vm", &self.r0vm_path),
+            receipt,
+            options,
+        )
+    }
+}
+
+// Generators of G1 and G2
+const MOCK_G1: [&str; 2] = [
+    "0000000000000000000000000000000000000000000000000000000000000001",
+    "0000000000000000000000000000000000000000000000000000000000000002",
+];
+const MOCK_G2: [&str; 4] = [
+    "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+    "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+    "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+    "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+];
+
+/// Groth16 seal of the mock prover: the generators of G1 and G2, valid points
+/// that do not verify
+pub fn mock_seal() -> Vec<u8> {
+    MOCK_G1
+        .iter()
+        .chain(MOCK_G2.iter())
+        .chain(MOCK_G1.iter())
+        .flat_map(|element| hex::decode(element).expect("valid hex constant"))
+        .collect()
+}
+
+/// Deterministic prover that executes the guest without proving it. Its
+/// composite and succinct receipts are fake receipts of the execution claim, only
+/// accepted in dev mode (`RISC0_DEV_MODE=1`), and its groth16 seal never verifies.
+pub struct MockProver;
+
+impl ZkProver for MockProver {
+    fn prove_stark(&self, input: u32, options: &ProveOptions) -> Result<Receipt, String> {
+        // The executor panics on an empty binary, left by RISC0_SKIP_BUILD
+        if BITVMX_ELF.is_empty() {
+            return Err("The guest is not built, the mock prover has nothing to execute".into());
+        }
+        let session = default_executor()
+            .execute(executor_env(input, options)?, BITVMX_ELF)
+            .map_err(|e| format!("Failed to execute the guest: {}", e))?;
+        let claim = session
+            .receipt_claim
+            .ok_or("The executor returned no receipt claim")?;
+        let receipt = Receipt::new(
+            InnerReceipt::Fake(FakeReceipt::new(claim)),
+            session.journal.bytes,
+        );
+
+        match options.receipt_kind {
+            ReceiptKind::Groth16 => self.compress(&receipt, options),
+            _ => Ok(receipt),
+        }
+    }
+
+    fn compress(&self, receipt: &Receipt, options: &ProveOptions) -> Result<Receipt, String> {
+        if options.receipt_kind != ReceiptKind::Groth16 {
+            return Ok(receipt.clone());
+        }
+
+        let claim = receipt
+            .claim()
+            .map_err(|e| format!("Failed to get receipt claim: {}", e))?;
+
+        let seal = mock_seal();
+        let parameters = Groth16ReceiptVerifierParameters::default().digest();
+
+        Ok(Receipt::new(
+            InnerReceipt::Groth16(Groth16Receipt::new(seal, claim, parameters)),
+            receipt.journal.bytes.clone(),
+        ))
+    }
+}
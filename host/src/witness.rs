@@ -0,0 +1,138 @@
+use methods::BITVMX_ID;
+use risc0_zkvm::sha::Digestible;
+use risc0_zkvm::{
+    Groth16ProofJson, Groth16Receipt, Groth16ReceiptVerifierParameters, InnerReceipt, Receipt,
+};
+use zk_result::ResultType;
+
+use crate::error::HostError;
+use crate::{read_receipt, save_result, serialize_receipt};
+
+/// Runs the identity_p254 recursion step on a succinct receipt, the last step
+/// before the groth16 wrap, and writes the resulting receipt together with the
+/// witness input of the groth16 circuit. The witness is the `input.json` that
+/// the risc0 groth16 prover reads.
+pub fn export_witness(
+    receipt_name: &str,
+    p254_output: &str,
+    witness_output: &str,
+) -> Result<(), HostError> {
+    let receipt = read_receipt(receipt_name)?;
+    let succinct = receipt.inner.succinct().map_err(|_| {
+        HostError::MalformedInput(format!(
+            "{} is not a succinct receipt, run compress --to succinct first",
+            receipt_name
+        ))
+    })?;
+
+    let p254 = risc0_zkvm::recursion::identity_p254(succinct)
+        .map_err(|e| HostError::Prover(format!("Failed to run identity_p254: {}", e)))?;
+
+    let witness = seal_to_witness(p254.get_seal_bytes().as_slice())?;
+    std::fs::write(witness_output, witness).map_err(|_| {
+        HostError::Io(format!(
+            "Failed to write witness to file: {}",
+            witness_output
+        ))
+    })?;
+
+    serialize_receipt(
+        Receipt::new(InnerReceipt::Succinct(p254), receipt.journal.bytes),
+        p254_output,
+    )?;
+
+    println!(
+        "The identity_p254 receipt was saved to {} and the witness to {}",
+        p254_output, witness_output
+    );
+    Ok(())
+}
+
+/// Converts the seal of an identity_p254 receipt into the witness input of the
+/// groth16 circuit, a JSON object with the `iop` array of the seal elements.
+pub fn seal_to_witness(seal: &[u8]) -> Result<Vec<u8>, HostError> {
+    let mut witness = Vec::new();
+    risc0_zkvm::seal_to_json(seal, &mut witness)
+        .map_err(|e| HostError::Prover(format!("Failed to convert the seal to JSON: {}", e)))?;
+    Ok(witness)
+}
+
+/// Reads a groth16 proof in the `proof.json` format of snarkjs and rapidsnark,
+/// which the risc0 groth16 prover also writes, and returns its seal bytes.
+/// Other formats are rejected; gnark proofs are read by `verifier import-gnark`
+/// instead.
+pub fn read_groth16_proof(proof_file: &str) -> Result<Vec<u8>, HostError> {
+    let invalid = |e: &dyn std::fmt::Display| {
+        HostError::MalformedInput(format!("Invalid proof file {}: {}", proof_file, e))
+    };
+    let proof = std::fs::read(proof_file)
+        .map_err(|_| HostError::Io(format!("Failed to read proof file: {}", proof_file)))?;
+    let unsupported = || {
+        invalid(
+            &"only the snarkjs proof.json format is supported, import gnark proofs with `verifier import-gnark`",
+        )
+    };
+    let proof: serde_json::Value = serde_json::from_slice(&proof).map_err(|_| unsupported())?;
+    if ["pi_a", "pi_b", "pi_c"]
+        .iter()
+        .any(|key| proof.get(key).is_none())
+    {
+        return Err(unsupported());
+    }
+    for (key, expected) in [("protocol", "groth16"), ("curve", "bn128")] {
+        if let Some(value) = proof.get(key) {
+            if value.as_str() != Some(expected) {
+                return Err(invalid(&format!(
+                    "the {} must be {}, found {}",
+                    key, expected, value
+                )));
+            }
+        }
+    }
+
+    let proof: Groth16ProofJson = serde_json::from_value(proof).map_err(|e| invalid(&e))?;
+    Ok(risc0_groth16::Seal::try_from(proof)
+        .map_err(|e| invalid(&e))?
+        .to_vec())
+}
+
+/// Reads a groth16 proof with [`read_groth16_proof`] and saves it as a
+/// zk-result seal once it verifies against the claim of the receipt it was
+/// proven for.
+pub fn import_groth16(
+    receipt_name: &str,
+    proof_file: &str,
+    seal_output: &str,
+) -> Result<(), HostError> {
+    let receipt = read_receipt(receipt_name)?;
+    let claim = receipt
+        .claim()
+        .map_err(|e| HostError::MalformedInput(format!("Failed to get receipt claim: {}", e)))?;
+
+    let seal = read_groth16_proof(proof_file)?;
+
+    let groth16_receipt = Receipt::new(
+        InnerReceipt::Groth16(Groth16Receipt::new(
+            seal.clone(),
+            claim,
+            Groth16ReceiptVerifierParameters::default().digest(),
+        )),
+        receipt.journal.bytes.clone(),
+    );
+    groth16_receipt.verify(BITVMX_ID).map_err(|e| {
+        HostError::InvalidProof(format!("The imported proof does not verify: {}", e))
+    })?;
+
+    save_result(
+        &ResultType::ProveResult {
+            seal,
+            status: "OK".to_string(),
+            journal: Some(receipt.journal.bytes),
+            options: None,
+        },
+        seal_output,
+    )?;
+
+    println!("The imported seal was saved to the file: {}", seal_output);
+    Ok(())
+}
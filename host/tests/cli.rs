@@ -0,0 +1,471 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use host::cache::{CacheEntry, ProofCache};
+use host::prover::{mock_seal, MockProver, ProveOptions, ZkProver};
+use host::witness::{read_groth16_proof, seal_to_witness};
+use methods::BITVMX_ID;
+use risc0_zkvm::{FakeReceipt, InnerReceipt, Receipt, ReceiptClaim};
+use zk_result::ResultType;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("host-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn host(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_host"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn read_result(path: PathBuf) -> ResultType {
+    ResultType::from_json_string(std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+// The mock prover executes the guest, which RISC0_SKIP_BUILD leaves empty. The
+// tests that need it are ignored, run them with `cargo test -- --ignored`.
+fn require_guest() {
+    assert!(!methods::BITVMX_ELF.is_empty(), "The guest is not built");
+}
+
+#[test]
+#[ignore = "executes the guest"]
+fn mock_prover_is_deterministic() {
+    require_guest();
+    let first = MockProver
+        .prove_stark(50, &ProveOptions::default())
+        .unwrap();
+    let second = MockProver
+        .prove_stark(50, &ProveOptions::default())
+        .unwrap();
+    assert_eq!(
+        bincode::serialize(&first).unwrap(),
+        bincode::serialize(&second).unwrap()
+    );
+
+    let snark = MockProver
+        .compress(&first, &ProveOptions::groth16())
+        .unwrap();
+    assert_eq!(snark.inner.groth16().unwrap().seal.len(), 256);
+    assert_eq!(snark.journal.bytes, first.journal.bytes);
+}
+
+#[test]
+#[ignore = "executes the guest"]
+fn prove_stark_writes_the_journal() {
+    require_guest();
+    let dir = temp_dir("stark");
+
+    for (input, journal) in [("50", [1, 0, 0, 0]), ("150", [0, 0, 0, 0])] {
+        let output = host(
+            &dir,
+            &[
+                "prove-stark",
+                "--prover",
+                "mock",
+                "--input",
+                input,
+                "--receipt",
+                "r.bin",
+                "--result",
+                "out.json",
+            ],
+        );
+        assert!(output.status.success());
+
+        let result = read_result(dir.join("out.json"));
+        assert_eq!(result.get_status(), "OK");
+        assert_eq!(result.get_journal(), Some(journal.to_vec()));
+        assert!(result.get_seal().is_empty());
+    }
+
+    // A receipt that cannot be written is an I/O error, not a prover failure
+    let output = host(
+        &dir,
+        &[
+            "prove-stark",
+            "--prover",
+            "mock",
+            "--input",
+            "50",
+            "--receipt",
+            "missing/r.bin",
+            "--result",
+            "out.json",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(74));
+    assert!(read_result(dir.join("out.json"))
+        .get_status()
+        .contains("Failed to write receipt"));
+}
+
+#[test]
+#[ignore = "executes the guest"]
+fn prove_snark_writes_the_seal() {
+    require_guest();
+    let dir = temp_dir("snark");
+
+    let output = host(
+        &dir,
+        &[
+            "prove-stark",
+            "--prover",
+            "mock",
+            "--input",
+            "7",
+            "-r",
+            "r.bin",
+        ],
+    );
+    assert!(output.status.success());
+    let output = host(
+        &dir,
+        &[
+            "prove-snark",
+            "--prover",
+            "mock",
+            "-r",
+            "r.bin",
+            "-s",
+            "s.json",
+        ],
+    );
+    assert!(output.status.success());
+
+    let result = read_result(dir.join("s.json"));
+    assert_eq!(result.get_status(), "OK");
+    assert_eq!(result.get_seal().len(), 256);
+    assert_eq!(result.get_journal(), Some(vec![1, 0, 0, 0]));
+}
+
+#[test]
+#[ignore = "executes the guest"]
+fn prove_options_are_recorded() {
+    require_guest();
+    let dir = temp_dir("options");
+
+    let output = host(
+        &dir,
+        &[
+            "prove-stark",
+            "--prover",
+            "mock",
+            "--input",
+            "7",
+            "--receipt",
+            "r.bin",
+            "--receipt-kind",
+            "succinct",
+            "--segment-limit-po2",
+            "18",
+            "--result",
+            "out.json",
+        ],
+    );
+    assert!(output.status.success());
+
+    let options = read_result(dir.join("out.json")).get_options().unwrap();
+    assert_eq!(options["receipt_kind"], "succinct");
+    assert_eq!(options["hash_fn"], "poseidon2");
+    assert_eq!(options["segment_limit_po2"], 18);
+    assert!(options.get("session_cycle_limit").is_none());
+}
+
+#[test]
+#[ignore = "executes the guest"]
+fn mock_receipt_verifies_in_dev_mode() {
+    require_guest();
+    let dir = temp_dir("verify");
+
+    host(
+        &dir,
+        &[
+            "prove-stark",
+            "--prover",
+            "mock",
+            "--input",
+            "1",
+            "-r",
+            "r.bin",
+        ],
+    );
+    let output = Command::new(env!("CARGO_BIN_EXE_host"))
+        .current_dir(&dir)
+        .env("RISC0_DEV_MODE", "1")
+        .args(["verify-stark", "-r", "r.bin"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Receipt verified successfully"));
+}
+
+#[test]
+fn dump_id_writes_eight_words() {
+    let dir = temp_dir("dump-id");
+
+    let output = host(&dir, &["dump-id", "-i", "image_id.json"]);
+    assert!(output.status.success());
+
+    let image_id =
+        json::parse(&std::fs::read_to_string(dir.join("image_id.json")).unwrap()).unwrap();
+    assert_eq!(image_id.len(), 8);
+}
+
+#[test]
+#[ignore = "executes the guest"]
+fn compress_writes_verified_receipts() {
+    require_guest();
+    let dir = temp_dir("compress");
+
+    let output = host(
+        &dir,
+        &[
+            "prove-stark",
+            "--prover",
+            "mock",
+            "--input",
+            "7",
+            "-r",
+            "r.bin",
+        ],
+    );
+    assert!(output.status.success());
+
+    // -i is the image id across the tools, never the input
+    let output = host(
+        &dir,
+        &["prove-stark", "--prover", "mock", "-i", "7", "-r", "r.bin"],
+    );
+    assert_eq!(output.status.code(), Some(2));
+
+    let compress = |receipt: &str, to: &str, output: &str| {
+        Command::new(env!("CARGO_BIN_EXE_host"))
+            .current_dir(&dir)
+            .env("RISC0_DEV_MODE", "1")
+            .args([
+                "compress", "--prover", "mock", "-r", receipt, "--to", to, "-o", output,
+            ])
+            .output()
+            .unwrap()
+    };
+
+    compress("r.bin", "succinct", "s.bin");
+    assert!(dir.join("s.bin").exists());
+
+    // The mock groth16 seal never verifies, so nothing is written
+    let output = compress("s.bin", "groth16", "g.bin");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("does not verify"));
+    assert!(!dir.join("g.bin").exists());
+
+    let output = compress("r.bin", "composite", "c.bin");
+    assert_eq!(output.status.code(), Some(65));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be compressed"));
+
+    let output = compress("missing.bin", "succinct", "m.bin");
+    assert_eq!(output.status.code(), Some(74));
+}
+
+// Errors go to stderr with the exit codes of the verifier
+#[test]
+fn cache_errors_exit_non_zero() {
+    let dir = temp_dir("cache-errors");
+    std::fs::write(dir.join("file"), "").unwrap();
+
+    let output = host(&dir, &["cache", "list", "--cache", "file"]);
+    assert_eq!(output.status.code(), Some(74));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to create cache directory"));
+    assert!(output.stdout.is_empty());
+
+    let output = host(
+        &dir,
+        &[
+            "prove-stark",
+            "--prover",
+            "mock",
+            "--input",
+            "7",
+            "-r",
+            "r.bin",
+            "--cache",
+            "file",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(74));
+    assert!(!dir.join("r.bin").exists());
+}
+
+// A fake receipt verifies in dev mode whatever prover was asked for
+#[test]
+fn fake_receipts_are_not_cached() {
+    let dir = temp_dir("cache-fake");
+    let cache = ProofCache::open(dir.to_str().unwrap()).unwrap();
+    let entry = CacheEntry::new("stark", &7u32.to_le_bytes(), "{}");
+
+    let journal = vec![1, 0, 0, 0];
+    let claim = ReceiptClaim::ok(BITVMX_ID, journal.clone());
+    let receipt = Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), journal);
+    cache.put(&entry, &receipt).unwrap();
+    assert!(cache.list().unwrap().is_empty());
+    assert!(cache.get(&entry).is_none());
+}
+
+// risc0-zkvm 2 only proves with poseidon2, so no command takes a hash function
+#[test]
+fn hash_functions_are_not_options() {
+    let dir = temp_dir("hash-fn");
+
+    let prove_stark: &[&str] = &["prove-stark", "--input", "7", "-r", "r.bin"];
+    let compress: &[&str] = &["compress", "-r", "r.bin", "-o", "s.bin", "--to", "succinct"];
+    let prove_snark: &[&str] = &["prove-snark", "-r", "r.bin", "-s", "s.json"];
+    for args in [prove_stark, compress, prove_snark] {
+        let output = host(&dir, &[args, &["--hash-fn", "sha-256"]].concat());
+        assert_eq!(output.status.code(), Some(2), "{:?}", output);
+        assert!(!dir.join("r.bin").exists());
+    }
+}
+
+// A fake receipt of the guest claim, made without executing the guest
+fn fake_receipt(dir: &Path, name: &str) {
+    let journal = vec![1, 0, 0, 0];
+    let claim = ReceiptClaim::ok(BITVMX_ID, journal.clone());
+    let receipt = Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), journal);
+    host::serialize_receipt(receipt, dir.join(name).to_str().unwrap()).unwrap();
+}
+
+// snarkjs proof.json of the generators of G1 and G2, the points of the mock seal
+const GENERATOR_PROOF: &str = r#"{
+    "pi_a": ["1", "2", "1"],
+    "pi_b": [
+        ["10857046999023057135944570762232829481370756359578518086990519993285655852781",
+         "11559732032986387107991004021392285783925812861821192530917403151452391805634"],
+        ["8495653923123431417604973247489272438418190587263600148770280649306958101930",
+         "4082367875863433681332203403145435568316851327593401208105741076214120093531"],
+        ["1", "0"]
+    ],
+    "pi_c": ["1", "2", "1"],
+    "protocol": "groth16",
+    "curve": "bn128"
+}"#;
+
+#[test]
+fn seal_is_converted_to_the_witness() {
+    // The identity_p254 seal is made of 55667 words encoding 25749 elements
+    let witness = seal_to_witness(&[0; 55667 * 4]).unwrap();
+    let witness: serde_json::Value = serde_json::from_slice(&witness).unwrap();
+    let iop = witness["iop"].as_array().unwrap();
+    assert_eq!(iop.len(), 25749);
+    assert!(iop.iter().all(|element| element == "0"));
+
+    assert!(matches!(
+        seal_to_witness(&[0; 1024]),
+        Err(host::error::HostError::Prover(_))
+    ));
+}
+
+#[test]
+fn export_witness_requires_a_succinct_receipt() {
+    let dir = temp_dir("export-witness");
+    fake_receipt(&dir, "r.bin");
+
+    let output = host(
+        &dir,
+        &[
+            "export-witness",
+            "-r",
+            "r.bin",
+            "--p254",
+            "p254.bin",
+            "-w",
+            "input.json",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(65), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not a succinct receipt"));
+    assert!(!dir.join("p254.bin").exists());
+    assert!(!dir.join("input.json").exists());
+}
+
+#[test]
+fn snarkjs_proof_is_read_as_a_seal() {
+    let dir = temp_dir("read-proof");
+    std::fs::write(dir.join("proof.json"), GENERATOR_PROOF).unwrap();
+
+    let seal = read_groth16_proof(dir.join("proof.json").to_str().unwrap()).unwrap();
+    assert_eq!(seal, mock_seal());
+}
+
+// A proof of other points than the ones proven for the claim does not verify
+#[test]
+fn import_rejects_proofs_that_do_not_verify() {
+    let dir = temp_dir("import-invalid");
+    fake_receipt(&dir, "r.bin");
+    let tampered = GENERATOR_PROOF.replacen(r#"["1", "2", "1"]"#, r#"["1", "3", "1"]"#, 1);
+
+    for proof in [GENERATOR_PROOF.to_string(), tampered] {
+        std::fs::write(dir.join("proof.json"), proof).unwrap();
+        let output = host(
+            &dir,
+            &[
+                "import-snark",
+                "-r",
+                "r.bin",
+                "-p",
+                "proof.json",
+                "-s",
+                "s.json",
+            ],
+        );
+        assert_eq!(output.status.code(), Some(1), "{:?}", output);
+        assert!(!dir.join("s.json").exists());
+    }
+}
+
+#[test]
+fn import_rejects_other_proof_formats() {
+    let dir = temp_dir("import-format");
+    fake_receipt(&dir, "r.bin");
+
+    let gnark = r#"{"Ar": {"X": "1", "Y": "2"}, "Krs": {"X": "1", "Y": "2"}}"#;
+    let plonk = GENERATOR_PROOF.replace(r#""groth16""#, r#""plonk""#);
+    for proof in [gnark.as_bytes(), plonk.as_bytes(), &[0x01, 0x02, 0x03]] {
+        std::fs::write(dir.join("proof.json"), proof).unwrap();
+        let output = host(
+            &dir,
+            &[
+                "import-snark",
+                "-r",
+                "r.bin",
+                "-p",
+                "proof.json",
+                "-s",
+                "s.json",
+            ],
+        );
+        assert_eq!(output.status.code(), Some(65), "{:?}", output);
+        assert!(!dir.join("s.json").exists());
+        if proof == gnark.as_bytes() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert!(stderr.contains("verifier import-gnark"), "{}", stderr);
+        }
+    }
+    let output = host(
+        &dir,
+        &[
+            "import-snark",
+            "-r",
+            "r.bin",
+            "-p",
+            "proof.json",
+            "-s",
+            "s.json",
+        ],
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("snarkjs proof.json"));
+}
@@ -0,0 +1,99 @@
+use risc0_groth16::Seal;
+use risc0_zkvm::sha::Digestible;
+use serde::{Deserialize, Serialize};
+
+use crate::error::VerifierError;
+use crate::format::{get_claim, read_file_to_string, write_file};
+use crate::get_default_parameters;
+use crate::signature::{verify_signature, BundleSignature};
+
+/// Everything a counterparty needs to verify a groth16 proof, in a single file.
+/// Digests, journal and seal are hex encoded, the image id keeps the words of
+/// the dumped image id file. A signed bundle has its signature checked when read.
+#[derive(Serialize, Deserialize)]
+pub struct ProofBundle {
+    pub risc0_version: String,
+    pub image_id: [u32; 8],
+    pub journal: String,
+    pub seal: String,
+    pub claim_digest: String,
+    pub control_root: String,
+    pub bn254_control_id: String,
+    /// Operator signature over every other field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<BundleSignature>,
+}
+
+impl ProofBundle {
+    pub fn new(image_id: &[u32; 8], journal: &[u8], seal: &Seal) -> Result<Self, VerifierError> {
+        let params = get_default_parameters()?;
+
+        Ok(ProofBundle {
+            risc0_version: risc0_zkvm::VERSION.to_string(),
+            image_id: *image_id,
+            journal: hex::encode(journal),
+            seal: hex::encode(seal.to_vec()),
+            claim_digest: hex::encode(get_claim(image_id, journal).digest()),
+            control_root: hex::encode(params.control_root),
+            bn254_control_id: hex::encode(params.bn254_control_id),
+            signature: None,
+        })
+    }
+
+    pub fn journal(&self) -> Result<Vec<u8>, VerifierError> {
+        hex::decode(&self.journal)
+            .map_err(|e| VerifierError::MalformedInput(format!("Invalid bundle journal: {}", e)))
+    }
+
+    pub fn seal(&self) -> Result<Seal, VerifierError> {
+        let seal = hex::decode(&self.seal)
+            .map_err(|e| VerifierError::MalformedInput(format!("Invalid bundle seal: {}", e)))?;
+        Seal::from_vec(&seal)
+            .map_err(|e| VerifierError::MalformedInput(format!("Invalid bundle seal: {}", e)))
+    }
+
+    // The recorded digests must match the ones this verifier computes, otherwise
+    // the bundle was edited or produced for other verifier parameters
+    fn check(&self) -> Result<(), VerifierError> {
+        let params = get_default_parameters()?;
+        let claim_digest = hex::encode(get_claim(&self.image_id, &self.journal()?).digest());
+
+        for (name, recorded, expected) in [
+            ("claim digest", &self.claim_digest, claim_digest),
+            (
+                "control root",
+                &self.control_root,
+                hex::encode(params.control_root),
+            ),
+            (
+                "BN254 control id",
+                &self.bn254_control_id,
+                hex::encode(params.bn254_control_id),
+            ),
+        ] {
+            if *recorded != expected {
+                return Err(VerifierError::InvalidBundle(format!(
+                    "{} {} does not match {} (bundle made with risc0 {})",
+                    name, recorded, expected, self.risc0_version
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn read_bundle(name: &str) -> Result<ProofBundle, VerifierError> {
+    let bundle: ProofBundle = serde_json::from_str(&read_file_to_string(name)?)
+        .map_err(|e| VerifierError::MalformedInput(format!("Invalid bundle {}: {}", name, e)))?;
+    bundle.check()?;
+    if let Some(signature) = &bundle.signature {
+        verify_signature(&bundle, signature)?;
+    }
+    Ok(bundle)
+}
+
+pub fn write_bundle(bundle: &ProofBundle, name: &str) -> Result<(), VerifierError> {
+    let json = serde_json::to_string_pretty(bundle)
+        .map_err(|e| VerifierError::Io(format!("Failed to serialize bundle: {}", e)))?;
+    write_file(name, json + "\n")
+}
@@ -0,0 +1,115 @@
+use ark_bn254::{Fq, Fq2, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+use risc0_groth16::Seal;
+use std::fmt;
+
+const FIELD_SIZE: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SealCheckError {
+    /// The seal does not have the expected number of coordinates
+    Malformed(String),
+    /// The coordinate is not encoded as exactly 32 big-endian bytes
+    NotCanonical(String),
+    /// The coordinate is not below the BN254 base field modulus
+    NotInField(String),
+    /// The point is the point at infinity
+    PointAtInfinity(String),
+    /// The point does not satisfy the curve equation
+    NotOnCurve(String),
+    /// The point is not in the prime order subgroup
+    NotInSubgroup(String),
+}
+
+impl fmt::Display for SealCheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SealCheckError::Malformed(element) => write!(f, "{} is malformed", element),
+            SealCheckError::NotCanonical(element) => {
+                write!(f, "{} is not encoded as {} bytes", element, FIELD_SIZE)
+            }
+            SealCheckError::NotInField(element) => {
+                write!(f, "{} is not below the BN254 modulus", element)
+            }
+            SealCheckError::PointAtInfinity(element) => {
+                write!(f, "{} is the point at infinity", element)
+            }
+            SealCheckError::NotOnCurve(element) => write!(f, "{} is not on the curve", element),
+            SealCheckError::NotInSubgroup(element) => {
+                write!(f, "{} is not in the correct subgroup", element)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SealCheckError {}
+
+// Parse a big-endian coordinate, rejecting anything that is not a canonical field element
+pub fn fq_from_be_bytes(bytes: &[u8], element: &str) -> Result<Fq, SealCheckError> {
+    if bytes.len() != FIELD_SIZE {
+        return Err(SealCheckError::NotCanonical(element.to_string()));
+    }
+    if bytes >= Fq::MODULUS.to_bytes_be().as_slice() {
+        return Err(SealCheckError::NotInField(element.to_string()));
+    }
+    Ok(Fq::from_be_bytes_mod_order(bytes))
+}
+
+pub fn check_g1(point: &[Vec<u8>], name: &str) -> Result<G1Affine, SealCheckError> {
+    if point.len() != 2 {
+        return Err(SealCheckError::Malformed(name.to_string()));
+    }
+    let x = fq_from_be_bytes(&point[0], &format!("{}.x", name))?;
+    let y = fq_from_be_bytes(&point[1], &format!("{}.y", name))?;
+
+    // risc0 encodes the point at infinity as (0, 0), which is never a valid proof element
+    if x == Fq::from(0u8) && y == Fq::from(0u8) {
+        return Err(SealCheckError::PointAtInfinity(name.to_string()));
+    }
+
+    let g1 = G1Affine::new_unchecked(x, y);
+    if !g1.is_on_curve() {
+        return Err(SealCheckError::NotOnCurve(name.to_string()));
+    }
+    if !g1.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(SealCheckError::NotInSubgroup(name.to_string()));
+    }
+    Ok(g1)
+}
+
+// The seal stores every Fq2 coordinate as [c1, c0]
+pub fn check_g2(point: &[Vec<Vec<u8>>], name: &str) -> Result<G2Affine, SealCheckError> {
+    if point.len() != 2 || point[0].len() != 2 || point[1].len() != 2 {
+        return Err(SealCheckError::Malformed(name.to_string()));
+    }
+    let x = Fq2::new(
+        fq_from_be_bytes(&point[0][1], &format!("{}.x.c0", name))?,
+        fq_from_be_bytes(&point[0][0], &format!("{}.x.c1", name))?,
+    );
+    let y = Fq2::new(
+        fq_from_be_bytes(&point[1][1], &format!("{}.y.c0", name))?,
+        fq_from_be_bytes(&point[1][0], &format!("{}.y.c1", name))?,
+    );
+
+    if x == Fq2::from(0u8) && y == Fq2::from(0u8) {
+        return Err(SealCheckError::PointAtInfinity(name.to_string()));
+    }
+
+    let g2 = G2Affine::new_unchecked(x, y);
+    if !g2.is_on_curve() {
+        return Err(SealCheckError::NotOnCurve(name.to_string()));
+    }
+    if !g2.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(SealCheckError::NotInSubgroup(name.to_string()));
+    }
+    Ok(g2)
+}
+
+/// Checks that every coordinate of the seal is a canonical BN254 field element
+/// and that A, B and C are valid points of the Groth16 groups.
+pub fn check_seal(seal: &Seal) -> Result<(), SealCheckError> {
+    let _ = check_g1(&seal.a, "A")?;
+    let _ = check_g2(&seal.b, "B")?;
+    let _ = check_g1(&seal.c, "C")?;
+    Ok(())
+}
@@ -1,7 +1,21 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use risc0_groth16::Seal;
 
-use crate::{proof_as_input, show_claim, template_proof, template_setup, verify};
-use risc0_zkp::verify::VerificationError;
+use crate::bundle::read_bundle;
+use crate::error::VerifierError;
+use crate::format::*;
+use crate::gnark::GnarkEncoding;
+use crate::output::{emit, CommandOutput};
+use crate::signature::SignatureScheme;
+use crate::winternitz::WinternitzHash;
+use crate::witness::{WitnessPacking, MAX_SCRIPT_ELEMENT_SIZE};
+use crate::{
+    create_bundle, evm_seal, export_bitcoin_witness, export_gnark, export_pairings, export_snarkjs,
+    generate_claim, generate_operator_key, generate_winternitz_key, import_bitcoin_witness,
+    import_gnark, import_snarkjs, list_public_inputs, merkle_check, merkle_commit, proof_as_input,
+    sign_proof_bundle, template_proof, template_setup, verify, verify_signed_bundle,
+    winternitz_script, winternitz_sign, winternitz_verify, ProofCheck,
+};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -10,111 +24,1195 @@ struct Cli {
     command: Option<Commands>,
 }
 
+// Expected journal produced by the stark. The group of the commands that take
+// it names every source, as clap groups do not reach into flattened structs.
+#[derive(Args)]
+#[group(skip)]
+pub struct JournalSource {
+    /// Journal bytes separated by commas
+    #[arg(short, long, value_delimiter = ',', num_args = 1..)]
+    journal: Option<Vec<u8>>,
+
+    /// Journal as a hex string
+    #[arg(long, value_name = "HEX")]
+    journal_hex: Option<String>,
+
+    /// Raw binary file with the journal
+    #[arg(long, value_name = "FILE")]
+    journal_file: Option<String>,
+
+    /// Stark receipt file that carries the journal
+    #[arg(long, value_name = "FILE")]
+    journal_receipt: Option<String>,
+
+    /// JSON result file written by the host
+    #[arg(long, value_name = "JSON_FILE")]
+    journal_result: Option<String>,
+
+    /// Journal committed by the guest as a single u32
+    #[arg(long, value_name = "U32")]
+    journal_u32: Option<u32>,
+}
+
+impl JournalSource {
+    fn get(&self) -> Result<Vec<u8>, VerifierError> {
+        if let Some(journal) = &self.journal {
+            Ok(journal.clone())
+        } else if let Some(journal) = &self.journal_hex {
+            get_journal_from_hex(journal)
+        } else if let Some(journal) = &self.journal_file {
+            get_journal_from_file(journal)
+        } else if let Some(journal) = &self.journal_receipt {
+            get_journal_from_receipt(journal)
+        } else if let Some(journal) = &self.journal_result {
+            get_journal_from_result(journal)
+        } else if let Some(journal) = self.journal_u32 {
+            Ok(get_journal_from_u32(journal))
+        } else {
+            unreachable!("clap requires one journal source or a bundle")
+        }
+    }
+}
+
+// Journal taken from exactly one source
+#[derive(Args)]
+#[group(
+    required = true,
+    multiple = false,
+    args = ["journal", "journal_hex", "journal_file", "journal_receipt", "journal_result", "journal_u32"]
+)]
+pub struct JournalSourceArgs {
+    #[command(flatten)]
+    source: JournalSource,
+}
+
+// Journal taken from exactly one source, or from a bundle that carries the
+// image id and the seal together with the journal
+#[derive(Args)]
+#[group(
+    required = true,
+    multiple = false,
+    args = ["journal", "journal_hex", "journal_file", "journal_receipt", "journal_result", "journal_u32", "bundle"]
+)]
+pub struct JournalArgs {
+    #[command(flatten)]
+    source: JournalSource,
+
+    /// Proof bundle with the image id, journal and seal
+    #[arg(short, long, value_name = "FILE")]
+    bundle: Option<String>,
+}
+
+// Image id, journal and seal of a command, from the bundle when there is one
+struct Proof {
+    image_id: Option<[u32; 8]>,
+    journal: Vec<u8>,
+    seal: Option<Seal>,
+}
+
+impl Proof {
+    fn read(
+        image_id: &Option<String>,
+        journal: &JournalArgs,
+        seal: &Option<String>,
+    ) -> Result<Self, VerifierError> {
+        if let Some(bundle) = &journal.bundle {
+            let bundle = read_bundle(bundle)?;
+            return Ok(Proof {
+                image_id: Some(bundle.image_id),
+                journal: bundle.journal()?,
+                seal: Some(bundle.seal()?),
+            });
+        }
+
+        Ok(Proof {
+            image_id: image_id.as_ref().map(get_image_id).transpose()?,
+            journal: journal.source.get()?,
+            seal: seal.as_deref().map(get_seal).transpose()?,
+        })
+    }
+
+    fn image_id(&self) -> &[u32; 8] {
+        self.image_id
+            .as_ref()
+            .expect("clap requires an image id or a bundle")
+    }
+
+    fn seal(&self) -> &Seal {
+        self.seal
+            .as_ref()
+            .expect("clap requires a seal or a bundle")
+    }
+
+    // The image id is only used for verification, so it is dropped when verification is skipped
+    fn proof_check(&self, skip_verify: bool) -> ProofCheck<'_> {
+        match skip_verify {
+            true => ProofCheck::SkipVerify,
+            false => ProofCheck::Verify(
+                self.image_id
+                    .as_ref()
+                    .expect("clap requires an image id unless verification is skipped"),
+            ),
+        }
+    }
+}
+
+// Where and how the result of a command is written
+#[derive(Args)]
+pub struct OutputArgs {
+    /// Print the result as JSON
+    #[arg(long, default_value = "false")]
+    json: bool,
+
+    /// Write the result to this file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<String>,
+}
+
+impl OutputArgs {
+    fn emit<T: CommandOutput>(&self, result: &T) -> Result<(), VerifierError> {
+        emit(result, self.json, self.output.as_ref())
+    }
+}
+
 #[derive(Subcommand)]
-enum Commands {
+pub enum Commands {
     /// Generate the claim to be verified
     GenerateClaim {
         /// File name with the dumped image id
-        #[arg(short, long, value_name = "FILE", required = true)]
-        image_id: String,
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
 
-        /// Expected journal produces by the stark
-        #[arg(short, long, value_delimiter=',', num_args = 1.., required=true)]
-        journal: Vec<u8>,
+        #[command(flatten)]
+        journal: JournalArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
     },
 
     /// Verify the claim
     Verify {
+        /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
+
+        #[command(flatten)]
+        journal: JournalArgs,
+
+        /// Groth16 proof file
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        seal: Option<String>,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Verify the proof and write it as a single bundle file
+    CreateBundle {
         /// File name with the dumped image id
         #[arg(short, long, value_name = "FILE", required = true)]
         image_id: String,
 
-        /// Expected journal produces by the stark
-        #[arg(short, long, value_delimiter=',', num_args = 1.., required=true)]
-        journal: Vec<u8>,
+        #[command(flatten)]
+        journal: JournalSourceArgs,
 
         /// Groth16 proof file
         #[arg(short, long, value_name = "FILE", required = true)]
         seal: String,
+
+        /// Output bundle file
+        #[arg(long, value_name = "FILE", required = true)]
+        bundle_out: String,
+
+        #[command(flatten)]
+        output: OutputArgs,
     },
 
-    TemplateSetup {
+    /// Generate an operator key to sign proof bundles
+    GenerateKey {
+        /// Signature scheme of the key
+        #[arg(long, value_enum, default_value_t = SignatureScheme::Bip340)]
+        scheme: SignatureScheme,
+
+        /// Output key file, it must not exist
+        #[arg(short, long, value_name = "FILE", required = true)]
+        key: String,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Verify the bundle proof and sign the bundle with the operator key
+    SignBundle {
+        /// Proof bundle file
+        #[arg(short, long, value_name = "FILE", required = true)]
+        bundle: String,
+
+        /// Operator key file
+        #[arg(short, long, value_name = "FILE", required = true)]
+        key: String,
+
+        /// Output signed bundle file, the input bundle is replaced when missing
+        #[arg(long, value_name = "FILE")]
+        bundle_out: Option<String>,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Verify the operator signature and the proof of a signed bundle
+    VerifySignedBundle {
+        /// Signed proof bundle file
+        #[arg(short, long, value_name = "FILE", required = true)]
+        bundle: String,
+
+        /// Public key the bundle must be signed with, in hex
+        #[arg(long, value_name = "HEX")]
+        operator: Option<String>,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Generate a Winternitz key to sign the journal and the compressed proof
+    WinternitzKeygen {
+        /// Hash function of the hash chains
+        #[arg(long, value_enum, default_value_t = WinternitzHash::Hash160)]
+        hash: WinternitzHash,
+
+        /// Bits signed by every hash chain, from 1 to 8
+        #[arg(long, default_value = "4")]
+        digit_bits: u8,
+
+        /// Size of the signed journal in bytes
+        #[arg(long, default_value = "4")]
+        journal_size: usize,
+
+        /// Output key file, it must not exist
+        #[arg(short, long, value_name = "FILE", required = true)]
+        key: String,
+
+        /// Output public keys file
+        #[arg(short, long, value_name = "FILE", required = true)]
+        public_keys: String,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Sign the journal and the compressed proof with a Winternitz key
+    WinternitzSign {
+        /// Winternitz key file
+        #[arg(short, long, value_name = "FILE", required = true)]
+        key: String,
+
         /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present_any = ["skip_verify", "bundle"],
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
+
+        #[command(flatten)]
+        journal: JournalArgs,
+
+        /// Groth16 proof file
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        seal: Option<String>,
+
+        /// Output signatures file
+        #[arg(long, value_name = "FILE", required = true)]
+        signatures: String,
+
+        /// Do not verify the proof against the image id and journal
+        #[arg(long, default_value = "false")]
+        skip_verify: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Verify the Winternitz signatures of the journal and the compressed proof
+    WinternitzVerify {
+        /// Public keys file
         #[arg(short, long, value_name = "FILE", required = true)]
-        image_id: String,
+        public_keys: String,
+
+        /// Signatures file
+        #[arg(long, value_name = "FILE", required = true)]
+        signatures: String,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Write the tapscripts that check the Winternitz signatures and their witness
+    WinternitzScript {
+        /// Public keys file
+        #[arg(short, long, value_name = "FILE", required = true)]
+        public_keys: String,
+
+        /// Signatures file to build the witness stacks from
+        #[arg(long, value_name = "FILE")]
+        signatures: Option<String>,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Fill the verifier constants in the template
+    TemplateSetup {
+        /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
+
+        /// Proof bundle to take the image id from
+        #[arg(short, long, value_name = "FILE")]
+        bundle: Option<String>,
 
         /// Initial template file
         #[arg(short, long, value_name = "FILE", required = true)]
         template: String,
 
-        /// Output file
-        #[arg(short, long, value_name = "FILE", required = true)]
-        output: String,
+        /// Output header file
+        #[arg(long, value_name = "FILE", required = true)]
+        header: String,
 
         /// Put in zero the journal and the proof
         #[arg(short, long, default_value = "false")]
         zero_proof: bool,
+
+        /// Fill the Miller loop line coefficients of gamma and delta
+        #[arg(long, default_value = "false")]
+        prepared_lines: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
     },
 
+    /// Fill the journal and the proof in the template
     TemplateProof {
-        /// Expected journal produces by the stark
-        #[arg(short, long, value_delimiter=',', num_args = 1.., required=true)]
-        journal: Vec<u8>,
+        /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present_any = ["skip_verify", "bundle"],
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
+
+        #[command(flatten)]
+        journal: JournalArgs,
 
         /// Groth16 proof file
-        #[arg(short, long, value_name = "FILE", required = true)]
-        seal: String,
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        seal: Option<String>,
 
         /// Initial template file
         #[arg(short, long, value_name = "FILE", required = true)]
         template: String,
 
-        /// Output file
-        #[arg(short, long, value_name = "FILE", required = true)]
-        output: String,
+        /// Output header file
+        #[arg(long, value_name = "FILE", required = true)]
+        header: String,
+
+        /// Do not verify the proof against the image id and journal
+        #[arg(long, default_value = "false")]
+        skip_verify: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
     },
 
+    /// Print the journal and the proof as the verifier program input
     ProofAsInput {
-        /// Expected journal produces by the stark
-        #[arg(short, long, value_delimiter=',', num_args = 1.., required=true)]
-        journal: Vec<u8>,
+        /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present_any = ["skip_verify", "bundle"],
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
+
+        #[command(flatten)]
+        journal: JournalArgs,
 
         /// Groth16 proof file
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        seal: Option<String>,
+
+        /// Do not verify the proof against the image id and journal
+        #[arg(long, default_value = "false")]
+        skip_verify: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Lay out the journal and the compressed proof as Bitcoin witness elements
+    ExportBitcoinWitness {
+        /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present_any = ["skip_verify", "bundle"],
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
+
+        #[command(flatten)]
+        journal: JournalArgs,
+
+        /// Groth16 proof file
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        seal: Option<String>,
+
+        /// How the bytes are packed in the elements
+        #[arg(long, value_enum, default_value_t = WitnessPacking::Byte)]
+        packing: WitnessPacking,
+
+        /// Maximum size of every element of the byte packing, up to 520 bytes
+        #[arg(long, default_value_t = MAX_SCRIPT_ELEMENT_SIZE)]
+        chunk_size: usize,
+
+        /// Do not verify the proof against the image id and journal
+        #[arg(long, default_value = "false")]
+        skip_verify: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Reassemble the journal and the seal from a witness stack
+    ImportBitcoinWitness {
+        /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "skip_verify"
+        )]
+        image_id: Option<String>,
+
+        /// Witness elements, bottom first, as a JSON array or separated by whitespace
+        #[arg(short, long, value_name = "FILE", required = true)]
+        witness: String,
+
+        /// How the bytes are packed in the elements
+        #[arg(long, value_enum, default_value_t = WitnessPacking::Byte)]
+        packing: WitnessPacking,
+
+        /// Output groth16 proof file
         #[arg(short, long, value_name = "FILE", required = true)]
         seal: String,
+
+        /// Do not verify the proof against the image id and the journal
+        #[arg(long, default_value = "false")]
+        skip_verify: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Print the seal and the verify arguments of the RISC Zero Solidity verifier
+    EvmSeal {
+        /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
+
+        #[command(flatten)]
+        journal: JournalArgs,
+
+        /// Groth16 proof file
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        seal: Option<String>,
+
+        /// Do not verify the proof against the image id and journal
+        #[arg(long, default_value = "false")]
+        skip_verify: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Build a SHA-256 Merkle tree over the proof input words
+    MerkleCommit {
+        /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present_any = ["skip_verify", "bundle"],
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
+
+        #[command(flatten)]
+        journal: JournalArgs,
+
+        /// Groth16 proof file
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        seal: Option<String>,
+
+        /// Bytes of every leaf, the last one padded with zeros
+        #[arg(long, default_value_t = 4)]
+        word_size: usize,
+
+        /// Do not verify the proof against the image id and journal
+        #[arg(long, default_value = "false")]
+        skip_verify: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Check a word of the proof input against the Merkle root
+    MerkleCheck {
+        /// Merkle root as a hex string
+        #[arg(long, value_name = "HEX", required = true)]
+        root: String,
+
+        /// Position of the word in the proof input, from zero
+        #[arg(long, required = true)]
+        index: usize,
+
+        /// Word as a hex string
+        #[arg(long, value_name = "HEX", required = true)]
+        word: String,
+
+        /// Sibling hashes separated by commas, the one of the leaf first
+        #[arg(long, value_name = "HEX", value_delimiter = ',')]
+        proof: Vec<String>,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Print the operands of the groth16 equation as a product of pairings
+    ExportPairings {
+        /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
+
+        #[command(flatten)]
+        journal: JournalArgs,
+
+        /// Groth16 proof file
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        seal: Option<String>,
+
+        /// Do not verify the proof against the image id and journal
+        #[arg(long, default_value = "false")]
+        skip_verify: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Print the public inputs of the groth16 circuit in decimal, hex and C bytes
+    PublicInputs {
+        /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
+
+        #[command(flatten)]
+        journal: JournalArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Write the proof, the public inputs and the verifying key in snarkjs format
+    ExportSnarkjs {
+        /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
+
+        #[command(flatten)]
+        journal: JournalArgs,
+
+        /// Groth16 proof file
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        seal: Option<String>,
+
+        /// Output proof file
+        #[arg(long, value_name = "JSON_FILE", default_value = "proof.json")]
+        proof: String,
+
+        /// Output public inputs file
+        #[arg(long, value_name = "JSON_FILE", default_value = "public.json")]
+        public: String,
+
+        /// Output verifying key file
+        #[arg(
+            long,
+            value_name = "JSON_FILE",
+            default_value = "verification_key.json"
+        )]
+        verification_key: String,
+
+        /// Do not verify the proof against the image id and journal
+        #[arg(long, default_value = "false")]
+        skip_verify: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Verify a snarkjs proof and write it as a groth16 proof file
+    ImportSnarkjs {
+        /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
+
+        #[command(flatten)]
+        journal: JournalArgs,
+
+        /// snarkjs proof file
+        #[arg(long, value_name = "JSON_FILE", required = true)]
+        proof: String,
+
+        /// snarkjs public inputs file, checked against the claim
+        #[arg(long, value_name = "JSON_FILE")]
+        public: Option<String>,
+
+        /// snarkjs verifying key file, checked against the risc0 verifying key
+        #[arg(long, value_name = "JSON_FILE")]
+        verification_key: Option<String>,
+
+        /// Output groth16 proof file
+        #[arg(short, long, value_name = "FILE", required = true)]
+        seal: String,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Write the proof and the verifying key in gnark binary format
+    ExportGnark {
+        /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present_any = ["skip_verify", "bundle"],
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
+
+        #[command(flatten)]
+        journal: JournalArgs,
+
+        /// Groth16 proof file
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present = "bundle",
+            conflicts_with = "bundle"
+        )]
+        seal: Option<String>,
+
+        /// Output proof file
+        #[arg(long, value_name = "FILE", default_value = "proof.bin")]
+        proof: String,
+
+        /// Output verifying key file
+        #[arg(long, value_name = "FILE", default_value = "verifying_key.bin")]
+        verification_key: String,
+
+        /// Point encoding of both files
+        #[arg(long, value_enum, default_value_t = GnarkEncoding::Compressed)]
+        encoding: GnarkEncoding,
+
+        /// Do not verify the proof against the image id and journal
+        #[arg(long, default_value = "false")]
+        skip_verify: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Read a gnark proof and write it as a groth16 proof file
+    ImportGnark {
+        /// File name with the dumped image id
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            required_unless_present_any = ["skip_verify", "bundle"],
+            conflicts_with = "bundle"
+        )]
+        image_id: Option<String>,
+
+        #[command(flatten)]
+        journal: JournalArgs,
+
+        /// gnark proof file, compressed or uncompressed
+        #[arg(long, value_name = "FILE", required = true)]
+        proof: String,
+
+        /// gnark verifying key file, checked against the risc0 verifying key
+        #[arg(long, value_name = "FILE")]
+        verification_key: Option<String>,
+
+        /// Output groth16 proof file
+        #[arg(short, long, value_name = "FILE", required = true)]
+        seal: String,
+
+        /// Do not verify the proof against the image id and journal
+        #[arg(long, default_value = "false")]
+        skip_verify: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
     },
 }
 
-pub fn run() -> Result<(), VerificationError> {
+pub fn run() -> Result<(), VerifierError> {
     let cli = Cli::parse();
 
     match &cli.command {
-        Some(Commands::GenerateClaim { image_id, journal }) => {
-            show_claim(image_id, journal);
+        Some(command) => execute(command)?,
+        None => {
+            println!("No command provided");
+        }
+    }
+
+    Ok(())
+}
+
+pub fn execute(command: &Commands) -> Result<(), VerifierError> {
+    match command {
+        Commands::GenerateClaim {
+            image_id,
+            journal,
+            output,
+        } => {
+            let proof = Proof::read(image_id, journal, &None)?;
+            output.emit(&generate_claim(proof.image_id(), &proof.journal)?)?
         }
-        Some(Commands::Verify {
+        Commands::Verify {
             image_id,
             journal,
             seal,
-        }) => {
-            verify(image_id, journal, seal)?;
+            output,
+        } => {
+            let proof = Proof::read(image_id, journal, seal)?;
+            output.emit(&verify(proof.image_id(), &proof.journal, proof.seal())?)?
         }
-        Some(Commands::TemplateSetup {
+        Commands::CreateBundle {
             image_id,
-            template,
+            journal,
+            seal,
+            bundle_out,
+            output,
+        } => output.emit(&create_bundle(
+            &get_image_id(image_id)?,
+            &journal.source.get()?,
+            &get_seal(seal)?,
+            bundle_out,
+        )?)?,
+        Commands::GenerateKey {
+            scheme,
+            key,
+            output,
+        } => output.emit(&generate_operator_key(*scheme, key)?)?,
+        Commands::SignBundle {
+            bundle,
+            key,
+            bundle_out,
+            output,
+        } => output.emit(&sign_proof_bundle(
+            bundle,
+            key,
+            bundle_out.as_ref().unwrap_or(bundle),
+        )?)?,
+        Commands::VerifySignedBundle {
+            bundle,
+            operator,
+            output,
+        } => output.emit(&verify_signed_bundle(bundle, operator.as_deref())?)?,
+        Commands::WinternitzKeygen {
+            hash,
+            digit_bits,
+            journal_size,
+            key,
+            public_keys,
             output,
+        } => output.emit(&generate_winternitz_key(
+            *hash,
+            *digit_bits,
+            *journal_size,
+            key,
+            public_keys,
+        )?)?,
+        Commands::WinternitzSign {
+            key,
+            image_id,
+            journal,
+            seal,
+            signatures,
+            skip_verify,
+            output,
+        } => {
+            let proof = Proof::read(image_id, journal, seal)?;
+            output.emit(&winternitz_sign(
+                key,
+                proof.proof_check(*skip_verify),
+                &proof.journal,
+                proof.seal(),
+                signatures,
+            )?)?
+        }
+        Commands::WinternitzVerify {
+            public_keys,
+            signatures,
+            output,
+        } => output.emit(&winternitz_verify(public_keys, signatures)?)?,
+        Commands::WinternitzScript {
+            public_keys,
+            signatures,
+            output,
+        } => output.emit(&winternitz_script(public_keys, signatures.as_deref())?)?,
+        Commands::TemplateSetup {
+            image_id,
+            bundle,
+            template,
+            header,
             zero_proof,
-        }) => template_setup(image_id, template, output, *zero_proof),
-        Some(Commands::TemplateProof {
+            prepared_lines,
+            output,
+        } => {
+            let image_id = match bundle {
+                Some(bundle) => read_bundle(bundle)?.image_id,
+                None => get_image_id(image_id.as_ref().expect("clap requires an image id"))?,
+            };
+            output.emit(&template_setup(
+                &image_id,
+                template,
+                header,
+                *zero_proof,
+                *prepared_lines,
+            )?)?
+        }
+        Commands::TemplateProof {
+            image_id,
             journal,
             seal,
             template,
+            header,
+            skip_verify,
             output,
-        }) => template_proof(journal, seal, template, output),
-        Some(Commands::ProofAsInput { journal, seal }) => proof_as_input(journal, seal),
-
-        None => {
-            println!("No command provided");
+        } => {
+            let proof = Proof::read(image_id, journal, seal)?;
+            output.emit(&template_proof(
+                proof.proof_check(*skip_verify),
+                &proof.journal,
+                proof.seal(),
+                template,
+                header,
+            )?)?
+        }
+        Commands::ProofAsInput {
+            image_id,
+            journal,
+            seal,
+            skip_verify,
+            output,
+        } => {
+            let proof = Proof::read(image_id, journal, seal)?;
+            output.emit(&proof_as_input(
+                proof.proof_check(*skip_verify),
+                &proof.journal,
+                proof.seal(),
+            )?)?
+        }
+        Commands::ExportBitcoinWitness {
+            image_id,
+            journal,
+            seal,
+            packing,
+            chunk_size,
+            skip_verify,
+            output,
+        } => {
+            let proof = Proof::read(image_id, journal, seal)?;
+            output.emit(&export_bitcoin_witness(
+                proof.proof_check(*skip_verify),
+                &proof.journal,
+                proof.seal(),
+                *packing,
+                *chunk_size,
+            )?)?
+        }
+        Commands::ImportBitcoinWitness {
+            image_id,
+            witness,
+            packing,
+            seal,
+            skip_verify,
+            output,
+        } => {
+            let image_id = match skip_verify {
+                true => None,
+                false => Some(get_image_id(
+                    image_id.as_ref().expect("clap requires an image id"),
+                )?),
+            };
+            let check = match &image_id {
+                Some(image_id) => ProofCheck::Verify(image_id),
+                None => ProofCheck::SkipVerify,
+            };
+            output.emit(&import_bitcoin_witness(check, witness, *packing, seal)?)?
+        }
+        Commands::EvmSeal {
+            image_id,
+            journal,
+            seal,
+            skip_verify,
+            output,
+        } => {
+            let proof = Proof::read(image_id, journal, seal)?;
+            output.emit(&evm_seal(
+                proof.image_id(),
+                &proof.journal,
+                proof.seal(),
+                *skip_verify,
+            )?)?
+        }
+        Commands::MerkleCommit {
+            image_id,
+            journal,
+            seal,
+            word_size,
+            skip_verify,
+            output,
+        } => {
+            let proof = Proof::read(image_id, journal, seal)?;
+            output.emit(&merkle_commit(
+                proof.proof_check(*skip_verify),
+                &proof.journal,
+                proof.seal(),
+                *word_size,
+            )?)?
+        }
+        Commands::MerkleCheck {
+            root,
+            index,
+            word,
+            proof,
+            output,
+        } => output.emit(&merkle_check(root, *index, word, proof)?)?,
+        Commands::ExportPairings {
+            image_id,
+            journal,
+            seal,
+            skip_verify,
+            output,
+        } => {
+            let proof = Proof::read(image_id, journal, seal)?;
+            output.emit(&export_pairings(
+                proof.image_id(),
+                &proof.journal,
+                proof.seal(),
+                *skip_verify,
+            )?)?
+        }
+        Commands::PublicInputs {
+            image_id,
+            journal,
+            output,
+        } => {
+            let proof = Proof::read(image_id, journal, &None)?;
+            output.emit(&list_public_inputs(proof.image_id(), &proof.journal)?)?
+        }
+        Commands::ExportSnarkjs {
+            image_id,
+            journal,
+            seal,
+            proof,
+            public,
+            verification_key,
+            skip_verify,
+            output,
+        } => {
+            let read = Proof::read(image_id, journal, seal)?;
+            output.emit(&export_snarkjs(
+                read.image_id(),
+                &read.journal,
+                read.seal(),
+                *skip_verify,
+                proof,
+                public,
+                verification_key,
+            )?)?
+        }
+        Commands::ImportSnarkjs {
+            image_id,
+            journal,
+            proof,
+            public,
+            verification_key,
+            seal,
+            output,
+        } => {
+            let read = Proof::read(image_id, journal, &None)?;
+            output.emit(&import_snarkjs(
+                read.image_id(),
+                &read.journal,
+                proof,
+                public.as_deref(),
+                verification_key.as_deref(),
+                seal,
+            )?)?
+        }
+        Commands::ExportGnark {
+            image_id,
+            journal,
+            seal,
+            proof,
+            verification_key,
+            encoding,
+            skip_verify,
+            output,
+        } => {
+            let read = Proof::read(image_id, journal, seal)?;
+            output.emit(&export_gnark(
+                read.proof_check(*skip_verify),
+                &read.journal,
+                read.seal(),
+                *encoding,
+                proof,
+                verification_key,
+            )?)?
+        }
+        Commands::ImportGnark {
+            image_id,
+            journal,
+            proof,
+            verification_key,
+            seal,
+            skip_verify,
+            output,
+        } => {
+            let read = Proof::read(image_id, journal, &None)?;
+            output.emit(&import_gnark(
+                read.proof_check(*skip_verify),
+                &read.journal,
+                proof,
+                verification_key.as_deref(),
+                seal,
+            )?)?
         }
     }
 
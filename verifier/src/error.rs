@@ -0,0 +1,64 @@
+use risc0_zkp::verify::VerificationError;
+use std::fmt;
+
+use crate::check::SealCheckError;
+
+pub use zk_result::{EXIT_INVALID_PROOF, EXIT_IO, EXIT_MALFORMED_INPUT};
+
+#[derive(Debug)]
+pub enum VerifierError {
+    Verification(VerificationError),
+    InvalidSeal(SealCheckError),
+    InvalidBundle(String),
+    InvalidSignature(String),
+    InvalidMerkleProof(String),
+    MalformedInput(String),
+    Io(String),
+}
+
+impl VerifierError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            VerifierError::Verification(VerificationError::ReceiptFormatError) => {
+                EXIT_MALFORMED_INPUT
+            }
+            VerifierError::Verification(_) => EXIT_INVALID_PROOF,
+            VerifierError::InvalidSeal(SealCheckError::Malformed(_))
+            | VerifierError::InvalidSeal(SealCheckError::NotCanonical(_)) => EXIT_MALFORMED_INPUT,
+            VerifierError::InvalidSeal(_) => EXIT_INVALID_PROOF,
+            VerifierError::InvalidBundle(_) => EXIT_INVALID_PROOF,
+            VerifierError::InvalidSignature(_) => EXIT_INVALID_PROOF,
+            VerifierError::InvalidMerkleProof(_) => EXIT_INVALID_PROOF,
+            VerifierError::MalformedInput(_) => EXIT_MALFORMED_INPUT,
+            VerifierError::Io(_) => EXIT_IO,
+        }
+    }
+}
+
+impl fmt::Display for VerifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifierError::Verification(e) => write!(f, "{}", e),
+            VerifierError::InvalidSeal(e) => write!(f, "Invalid seal: {}", e),
+            VerifierError::InvalidBundle(e) => write!(f, "Invalid bundle: {}", e),
+            VerifierError::InvalidSignature(e) => write!(f, "Invalid signature: {}", e),
+            VerifierError::InvalidMerkleProof(e) => write!(f, "Invalid Merkle proof: {}", e),
+            VerifierError::MalformedInput(e) => write!(f, "Malformed input: {}", e),
+            VerifierError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for VerifierError {}
+
+impl From<VerificationError> for VerifierError {
+    fn from(e: VerificationError) -> Self {
+        VerifierError::Verification(e)
+    }
+}
+
+impl From<SealCheckError> for VerifierError {
+    fn from(e: SealCheckError) -> Self {
+        VerifierError::InvalidSeal(e)
+    }
+}
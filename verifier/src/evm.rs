@@ -0,0 +1,47 @@
+use risc0_groth16::Seal;
+use risc0_zkp::core::digest::Digest;
+use risc0_zkvm::sha::Digestible;
+use risc0_zkvm::Groth16ReceiptVerifierParameters;
+
+const WORD_SIZE: usize = 32;
+
+/// Selector of the RISC Zero Solidity verifier router: the first four bytes of
+/// the digest of the groth16 verifier parameters
+pub fn selector(params: &Groth16ReceiptVerifierParameters) -> [u8; 4] {
+    params.digest().as_bytes()[..4]
+        .try_into()
+        .expect("digests have more than four bytes")
+}
+
+/// Seal in the layout the Solidity verifier decodes: the selector followed by
+/// `abi.encode(a, b, c)`. The arrays are static, so their words are laid out
+/// in order without offsets, which is the order of the risc0 seal, with the
+/// imaginary part of every G2 coordinate first.
+pub fn encode_seal(params: &Groth16ReceiptVerifierParameters, seal: &Seal) -> Vec<u8> {
+    [selector(params).as_slice(), &seal.to_vec()].concat()
+}
+
+fn pad_to_word(mut bytes: Vec<u8>) -> Vec<u8> {
+    bytes.resize(bytes.len().div_ceil(WORD_SIZE) * WORD_SIZE, 0);
+    bytes
+}
+
+fn word(value: usize) -> Vec<u8> {
+    let mut word = vec![0u8; WORD_SIZE];
+    word[WORD_SIZE - 8..].copy_from_slice(&(value as u64).to_be_bytes());
+    word
+}
+
+/// Arguments of `verify(bytes seal, bytes32 imageId, bytes32 journalDigest)`,
+/// ABI-encoded without the function selector
+pub fn encode_verify_arguments(seal: &[u8], image_id: &Digest, journal_digest: &Digest) -> Vec<u8> {
+    [
+        // The seal is dynamic, its offset comes after the three head words
+        word(3 * WORD_SIZE),
+        image_id.as_bytes().to_vec(),
+        journal_digest.as_bytes().to_vec(),
+        word(seal.len()),
+        pad_to_word(seal.to_vec()),
+    ]
+    .concat()
+}
@@ -1,17 +1,40 @@
+use ark_bn254::{Fq12, Fq2, G1Affine, G2Affine};
+use ark_ec::bn::G2Prepared;
+use ark_ff::{BigInteger, PrimeField};
 use risc0_groth16::Seal;
 use risc0_zkp::core::digest::Digest;
 use risc0_zkvm::{MaybePruned, Receipt, ReceiptClaim};
-use std::io::Read;
 use zk_result::ResultType;
 
 use num_bigint::BigInt;
 use std::str::FromStr;
 
-pub fn deserialize_receipt(name: &str) -> Receipt {
+use crate::check::{check_g1, check_g2, fq_from_be_bytes, SealCheckError};
+use crate::error::VerifierError;
+
+pub fn read_file(name: &str) -> Result<Vec<u8>, VerifierError> {
+    std::fs::read(name).map_err(|e| VerifierError::Io(format!("Failed to read {}: {}", name, e)))
+}
+
+pub fn read_file_to_string(name: &str) -> Result<String, VerifierError> {
+    std::fs::read_to_string(name)
+        .map_err(|e| VerifierError::Io(format!("Failed to read {}: {}", name, e)))
+}
+
+pub fn write_file(name: &str, contents: impl AsRef<[u8]>) -> Result<(), VerifierError> {
+    std::fs::write(name, contents)
+        .map_err(|e| VerifierError::Io(format!("Failed to write {}: {}", name, e)))
+}
+
+pub fn deserialize_receipt(name: &str) -> Result<Receipt, VerifierError> {
     //deserialize receipt from file using bin code
-    let path = std::path::Path::new(name);
-    let receipt_bytes = std::fs::read(path).unwrap();
-    bincode::deserialize(&receipt_bytes).unwrap()
+    let receipt_bytes = read_file(name)?;
+    bincode::deserialize(&receipt_bytes)
+        .map_err(|e| VerifierError::MalformedInput(format!("Invalid receipt {}: {}", name, e)))
+}
+
+fn get_result(name: &str) -> Result<ResultType, VerifierError> {
+    ResultType::from_json_string(read_file_to_string(name)?).map_err(VerifierError::MalformedInput)
 }
 
 pub fn split_digest_custom(d: Digest) -> (u128, u128) {
@@ -33,50 +56,99 @@ pub fn bytes_to_str(bytes: &[u8]) -> String {
 }
 
 // Convert the U256 value to a byte array in big-endian format
-pub fn from_u256_bigint(value: &str) -> Vec<u8> {
-    //to_fixed_array(hex::decode(value).unwrap()).to_vec()
-    to_fixed_array(BigInt::from_str(value).unwrap().to_bytes_be().1).to_vec()
+pub fn from_u256_bigint(value: &str) -> Result<Vec<u8>, VerifierError> {
+    let value = BigInt::from_str(value)
+        .map_err(|e| VerifierError::MalformedInput(format!("Invalid U256 {}: {}", value, e)))?;
+    Ok(to_fixed_array(value.to_bytes_be().1)?.to_vec())
 }
 
-pub fn to_fixed_array(input: Vec<u8>) -> [u8; 32] {
+// Refuses to silently drop the high bytes of values that do not fit in 32 bytes
+pub fn to_fixed_array(input: Vec<u8>) -> Result<[u8; 32], VerifierError> {
+    if input.len() > 32 {
+        return Err(VerifierError::MalformedInput(format!(
+            "Value of {} bytes does not fit in 32 bytes",
+            input.len()
+        )));
+    }
     let mut fixed_array = [0u8; 32];
-    let start = core::cmp::max(32, input.len()) - core::cmp::min(32, input.len());
-    fixed_array[start..].copy_from_slice(&input[input.len().saturating_sub(32)..]);
-    fixed_array
+    fixed_array[32 - input.len()..].copy_from_slice(&input);
+    Ok(fixed_array)
 }
 
-pub fn get_image_id(image_id: &String) -> [u32; 8] {
-    let mut file = std::fs::File::open(image_id).unwrap();
-    let mut image_id_json = String::new();
-    file.read_to_string(&mut image_id_json).unwrap();
-    let value = json::parse(&image_id_json).unwrap();
+pub fn get_image_id(image_id: &String) -> Result<[u32; 8], VerifierError> {
+    let image_id_json = read_file_to_string(image_id)?;
+    let value = json::parse(&image_id_json).map_err(|e| {
+        VerifierError::MalformedInput(format!("Invalid image id {}: {}", image_id, e))
+    })?;
+    if !value.is_array() || value.len() != 8 {
+        return Err(VerifierError::MalformedInput(format!(
+            "Image id {} must be an array of 8 words",
+            image_id
+        )));
+    }
     //map the vector inside values to [u32,8]
-    let mut image_id: [u32; 8] = [0; 8];
+    let mut image_id_words: [u32; 8] = [0; 8];
     for (i, v) in value.members().enumerate() {
-        image_id[i] = v.as_u32().unwrap();
+        image_id_words[i] = v.as_u32().ok_or_else(|| {
+            VerifierError::MalformedInput(format!("Image id {} must contain u32 words", image_id))
+        })?;
     }
-    image_id
+    Ok(image_id_words)
+}
+
+pub fn get_journal_from_receipt(receipt: &str) -> Result<Vec<u8>, VerifierError> {
+    Ok(deserialize_receipt(receipt)?.journal.bytes)
+}
+
+pub fn get_journal_from_result(result: &str) -> Result<Vec<u8>, VerifierError> {
+    get_result(result)?.get_journal().ok_or_else(|| {
+        VerifierError::MalformedInput(format!("Result file {} does not carry a journal", result))
+    })
+}
+
+pub fn get_journal_from_file(name: &str) -> Result<Vec<u8>, VerifierError> {
+    read_file(name)
+}
+
+pub fn get_journal_from_hex(journal: &str) -> Result<Vec<u8>, VerifierError> {
+    hex::decode(journal.trim_start_matches("0x"))
+        .map_err(|e| VerifierError::MalformedInput(format!("Invalid journal hex: {}", e)))
+}
+
+// Encode the value the same way env::commit does inside the guest
+pub fn get_journal_from_u32(value: u32) -> Vec<u8> {
+    risc0_zkvm::serde::to_vec(&value)
+        .expect("u32 is always serializable")
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect()
 }
 
-pub fn get_claim(image_id: &String, journal: &Vec<u8>) -> ReceiptClaim {
-    let image_id = get_image_id(image_id);
-    let digest = Digest::new(image_id);
+pub fn get_claim(image_id: &[u32; 8], journal: &[u8]) -> ReceiptClaim {
+    let digest = Digest::new(*image_id);
 
-    let journal_maybe = MaybePruned::Value(journal.clone());
-    let claim = ReceiptClaim::ok(digest, journal_maybe);
-    claim
+    let journal_maybe = MaybePruned::Value(journal.to_vec());
+    ReceiptClaim::ok(digest, journal_maybe)
 }
 
-pub fn get_seal(proof: &str) -> Seal {
-    let mut file = std::fs::File::open(proof).unwrap();
-    let mut json_content = String::new();
-    file.read_to_string(&mut json_content).unwrap();
+pub fn get_seal(proof: &str) -> Result<Seal, VerifierError> {
+    let seal_vec = get_result(proof)?.get_seal();
 
-    let seal_vec = ResultType::from_json_string(json_content)
-        .unwrap()
-        .get_seal();
+    Seal::from_vec(&seal_vec)
+        .map_err(|e| VerifierError::MalformedInput(format!("Invalid seal {}: {}", proof, e)))
+}
 
-    Seal::from_vec(&seal_vec).unwrap()
+// Writes the seal as the JSON result file of the host
+pub fn write_seal(seal: &Seal, journal: &[u8], name: &str) -> Result<(), VerifierError> {
+    let result = ResultType::ProveResult {
+        seal: seal.to_vec(),
+        status: "OK".to_string(),
+        journal: Some(journal.to_vec()),
+        options: None,
+    };
+    let json = serde_json::to_string(&result)
+        .map_err(|e| VerifierError::Io(format!("Failed to serialize seal: {}", e)))?;
+    write_file(name, json)
 }
 
 pub fn g1_to_c_bytes(mut g1: Vec<Vec<u8>>) -> Vec<u8> {
@@ -100,6 +172,114 @@ pub fn g2_to_c_bytes(g2: Vec<Vec<Vec<u8>>>) -> Vec<u8> {
     bytes_g2
 }
 
+fn fq_to_be_bytes<F: PrimeField>(fq: &F) -> Vec<u8> {
+    fq.into_bigint().to_bytes_be()
+}
+
+pub fn g1_affine_to_c_bytes(point: &G1Affine) -> Vec<u8> {
+    g1_to_c_bytes(g1_to_seal(point))
+}
+
+pub fn g2_affine_to_c_bytes(point: &G2Affine) -> Vec<u8> {
+    g2_to_c_bytes(g2_to_seal(point))
+}
+
+fn g1_to_seal(point: &G1Affine) -> Vec<Vec<u8>> {
+    vec![fq_to_be_bytes(&point.x), fq_to_be_bytes(&point.y)]
+}
+
+fn g2_to_seal(point: &G2Affine) -> Vec<Vec<Vec<u8>>> {
+    vec![
+        vec![fq_to_be_bytes(&point.x.c1), fq_to_be_bytes(&point.x.c0)],
+        vec![fq_to_be_bytes(&point.y.c1), fq_to_be_bytes(&point.y.c0)],
+    ]
+}
+
+// Little endian coordinate with the parity flag in its top bit, as big endian
+// bytes and the flag
+fn c_bytes_to_be(bytes: &[u8]) -> (Vec<u8>, bool) {
+    let mut be: Vec<u8> = bytes.iter().rev().copied().collect();
+    let odd = be[0] & 128 != 0;
+    be[0] &= 127;
+    (be, odd)
+}
+
+// Inverse of g1_to_c_bytes: y is the root of the curve equation with the
+// flagged parity
+fn g1_from_c_bytes(bytes: &[u8], name: &str) -> Result<G1Affine, VerifierError> {
+    let (x, odd) = c_bytes_to_be(bytes);
+    let x = fq_from_be_bytes(&x, &format!("{}.x", name))?;
+    let point = G1Affine::get_point_from_x_unchecked(x, true)
+        .ok_or_else(|| SealCheckError::NotOnCurve(name.to_string()))?;
+    let point = match point.y.into_bigint().is_odd() == odd {
+        true => point,
+        false => -point,
+    };
+    Ok(check_g1(&g1_to_seal(&point), name)?)
+}
+
+// Inverse of g2_to_c_bytes, the flag is the parity of y.c0
+fn g2_from_c_bytes(bytes: &[u8], name: &str) -> Result<G2Affine, VerifierError> {
+    let (c1, odd) = c_bytes_to_be(&bytes[32..]);
+    // x.c0 carries no flag, so a set top bit leaves it out of the field
+    let c0: Vec<u8> = bytes[..32].iter().rev().copied().collect();
+    let x = Fq2::new(
+        fq_from_be_bytes(&c0, &format!("{}.x.c0", name))?,
+        fq_from_be_bytes(&c1, &format!("{}.x.c1", name))?,
+    );
+    let point = G2Affine::get_point_from_x_unchecked(x, true)
+        .ok_or_else(|| SealCheckError::NotOnCurve(name.to_string()))?;
+    let point = match point.y.c0.into_bigint().is_odd() == odd {
+        true => point,
+        false => -point,
+    };
+    Ok(check_g2(&g2_to_seal(&point), name)?)
+}
+
+/// Seal back from the 128 bytes of generate_proof_bytes_from_seal: A, B and C
+/// in the C layout
+pub fn seal_from_c_bytes(proof: &[u8]) -> Result<Seal, VerifierError> {
+    if proof.len() != 128 {
+        return Err(VerifierError::MalformedInput(format!(
+            "The compressed proof has 128 bytes, got {}",
+            proof.len()
+        )));
+    }
+    Ok(Seal {
+        a: g1_to_seal(&g1_from_c_bytes(&proof[..32], "A")?),
+        b: g2_to_seal(&g2_from_c_bytes(&proof[32..96], "B")?),
+        c: g1_to_seal(&g1_from_c_bytes(&proof[96..], "C")?),
+    })
+}
+
+fn fq2_to_c_bytes(fq2: &Fq2) -> Vec<u8> {
+    [fq2.c0, fq2.c1]
+        .iter()
+        .flat_map(|fq| fq.into_bigint().to_bytes_le())
+        .collect()
+}
+
+// Twelve elements of 32 little endian bytes, following the tower: c0 before
+// c1 in Fq12, Fq6 and Fq2
+pub fn fq12_to_c_bytes(fq12: &Fq12) -> Vec<u8> {
+    [fq12.c0, fq12.c1]
+        .iter()
+        .flat_map(|fq6| [fq6.c0, fq6.c1, fq6.c2])
+        .flat_map(|fq2| fq2_to_c_bytes(&fq2))
+        .collect()
+}
+
+// The three Fq2 coefficients of every line of the Miller loop, in the order
+// the loop consumes them
+pub fn g2_lines_to_c_bytes(prepared: &G2Prepared<ark_bn254::Config>) -> Vec<u8> {
+    prepared
+        .ell_coeffs
+        .iter()
+        .flat_map(|(c0, c1, c2)| [c0, c1, c2])
+        .flat_map(fq2_to_c_bytes)
+        .collect()
+}
+
 pub fn split_g1(data: String) -> Vec<String> {
     let parts: Vec<&str> = data
         .trim_matches(|c| c == '(' || c == ')')
@@ -111,15 +291,18 @@ pub fn split_g1(data: String) -> Vec<String> {
     vec![first_part.to_string(), second_part.to_string()]
 }
 
-pub fn g1_strings_to_vec(data: Vec<String>) -> Vec<Vec<u8>> {
-    vec![from_u256_bigint(&data[0]), from_u256_bigint(&data[1])]
+pub fn g1_strings_to_vec(data: Vec<String>) -> Result<Vec<Vec<u8>>, VerifierError> {
+    Ok(vec![
+        from_u256_bigint(&data[0])?,
+        from_u256_bigint(&data[1])?,
+    ])
 }
 
-pub fn g2_strings_to_vec(data: Vec<String>) -> Vec<Vec<Vec<u8>>> {
-    vec![
-        vec![from_u256_bigint(&data[0]), from_u256_bigint(&data[1])],
-        vec![from_u256_bigint(&data[2]), from_u256_bigint(&data[3])],
-    ]
+pub fn g2_strings_to_vec(data: Vec<String>) -> Result<Vec<Vec<Vec<u8>>>, VerifierError> {
+    Ok(vec![
+        vec![from_u256_bigint(&data[0])?, from_u256_bigint(&data[1])?],
+        vec![from_u256_bigint(&data[2])?, from_u256_bigint(&data[3])?],
+    ])
 }
 
 pub fn split_g2(data: String) -> Vec<String> {
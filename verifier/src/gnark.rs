@@ -0,0 +1,281 @@
+use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+use clap::ValueEnum;
+use risc0_groth16::Seal;
+use serde::Serialize;
+
+use crate::check::{check_g1, check_g2, fq_from_be_bytes};
+use crate::error::VerifierError;
+
+// The two spare bits of the first byte of every point tell how it is encoded,
+// as in gnark-crypto
+const FLAG_MASK: u8 = 0b11 << 6;
+const UNCOMPRESSED: u8 = 0b00 << 6;
+const COMPRESSED_INFINITY: u8 = 0b01 << 6;
+const COMPRESSED_SMALLEST: u8 = 0b10 << 6;
+const COMPRESSED_LARGEST: u8 = 0b11 << 6;
+
+const FIELD_SIZE: usize = 32;
+
+#[derive(Serialize, ValueEnum, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum GnarkEncoding {
+    /// Only the x coordinate and the sign of y, as written by `WriteTo`
+    Compressed,
+    /// Both coordinates, as written by `WriteRawTo`
+    Uncompressed,
+}
+
+fn malformed(name: &str, e: impl std::fmt::Display) -> VerifierError {
+    VerifierError::MalformedInput(format!("Invalid gnark {}: {}", name, e))
+}
+
+fn fq_bytes(fq: &Fq) -> Vec<u8> {
+    fq.into_bigint().to_bytes_be()
+}
+
+fn fq2_bytes(fq2: &Fq2) -> Vec<u8> {
+    [fq_bytes(&fq2.c1), fq_bytes(&fq2.c0)].concat()
+}
+
+// Lexicographic order is the integer order for Fq, and compares c1 before c0
+// for Fq2, the same in gnark-crypto and arkworks
+fn compressed_flag<F: Ord + std::ops::Neg<Output = F> + Copy>(y: F) -> u8 {
+    match y > -y {
+        true => COMPRESSED_LARGEST,
+        false => COMPRESSED_SMALLEST,
+    }
+}
+
+fn write_g1(out: &mut Vec<u8>, point: &G1Affine, encoding: GnarkEncoding) {
+    let mut bytes = match (point.is_zero(), encoding) {
+        (true, GnarkEncoding::Compressed) => vec![0u8; FIELD_SIZE],
+        (true, GnarkEncoding::Uncompressed) => vec![0u8; 2 * FIELD_SIZE],
+        (false, GnarkEncoding::Compressed) => fq_bytes(&point.x),
+        (false, GnarkEncoding::Uncompressed) => [fq_bytes(&point.x), fq_bytes(&point.y)].concat(),
+    };
+    bytes[0] |= match (point.is_zero(), encoding) {
+        (_, GnarkEncoding::Uncompressed) => UNCOMPRESSED,
+        (true, GnarkEncoding::Compressed) => COMPRESSED_INFINITY,
+        (false, GnarkEncoding::Compressed) => compressed_flag(point.y),
+    };
+    out.extend(bytes);
+}
+
+fn write_g2(out: &mut Vec<u8>, point: &G2Affine, encoding: GnarkEncoding) {
+    let mut bytes = match (point.is_zero(), encoding) {
+        (true, GnarkEncoding::Compressed) => vec![0u8; 2 * FIELD_SIZE],
+        (true, GnarkEncoding::Uncompressed) => vec![0u8; 4 * FIELD_SIZE],
+        (false, GnarkEncoding::Compressed) => fq2_bytes(&point.x),
+        (false, GnarkEncoding::Uncompressed) => [fq2_bytes(&point.x), fq2_bytes(&point.y)].concat(),
+    };
+    bytes[0] |= match (point.is_zero(), encoding) {
+        (_, GnarkEncoding::Uncompressed) => UNCOMPRESSED,
+        (true, GnarkEncoding::Compressed) => COMPRESSED_INFINITY,
+        (false, GnarkEncoding::Compressed) => compressed_flag(point.y),
+    };
+    out.extend(bytes);
+}
+
+fn write_u32(out: &mut Vec<u8>, value: usize) {
+    out.extend((value as u32).to_be_bytes());
+}
+
+// Reads the points one by one, each with the size its flag tells
+struct Reader<'a> {
+    bytes: &'a [u8],
+    name: &'a str,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, size: usize) -> Result<&'a [u8], VerifierError> {
+        if self.bytes.len() < size {
+            return Err(malformed(self.name, "unexpected end of data"));
+        }
+        let (taken, rest) = self.bytes.split_at(size);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<usize, VerifierError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes(bytes.try_into().expect("four bytes")) as usize)
+    }
+
+    // The first coordinate with the flag bits cleared
+    fn coordinates(&mut self, size: usize) -> Result<(u8, Vec<u8>), VerifierError> {
+        let flag = self.bytes.first().map(|byte| byte & FLAG_MASK);
+        let flag = flag.ok_or_else(|| malformed(self.name, "unexpected end of data"))?;
+        let size = match flag {
+            UNCOMPRESSED => 2 * size,
+            _ => size,
+        };
+        let mut bytes = self.take(size)?.to_vec();
+        bytes[0] &= !FLAG_MASK;
+        Ok((flag, bytes))
+    }
+
+    // gnark writes the point at infinity as zeros, flagged when compressed
+    fn is_infinity(&self, flag: u8, bytes: &[u8], element: &str) -> Result<bool, VerifierError> {
+        let zero = bytes.iter().all(|byte| *byte == 0);
+        match flag {
+            COMPRESSED_INFINITY if !zero => Err(malformed(
+                self.name,
+                format!("{} is flagged as infinity but is not zero", element),
+            )),
+            COMPRESSED_INFINITY => Ok(true),
+            UNCOMPRESSED => Ok(zero),
+            _ => Ok(false),
+        }
+    }
+
+    fn g1(&mut self, element: &str) -> Result<G1Affine, VerifierError> {
+        let (flag, bytes) = self.coordinates(FIELD_SIZE)?;
+        if self.is_infinity(flag, &bytes, element)? {
+            return Ok(G1Affine::zero());
+        }
+
+        let x = fq_from_be_bytes(&bytes[..FIELD_SIZE], element)?;
+        let point = match flag {
+            UNCOMPRESSED => {
+                G1Affine::new_unchecked(x, fq_from_be_bytes(&bytes[FIELD_SIZE..], element)?)
+            }
+            _ => G1Affine::get_point_from_x_unchecked(x, flag == COMPRESSED_LARGEST)
+                .ok_or_else(|| malformed(self.name, format!("{} is not on the curve", element)))?,
+        };
+        let _ = check_g1(&g1_seal(&point), element)?;
+        Ok(point)
+    }
+
+    fn g2(&mut self, element: &str) -> Result<G2Affine, VerifierError> {
+        let (flag, bytes) = self.coordinates(2 * FIELD_SIZE)?;
+        if self.is_infinity(flag, &bytes, element)? {
+            return Ok(G2Affine::zero());
+        }
+
+        let fq2 = |bytes: &[u8]| -> Result<Fq2, VerifierError> {
+            Ok(Fq2::new(
+                fq_from_be_bytes(&bytes[FIELD_SIZE..], element)?,
+                fq_from_be_bytes(&bytes[..FIELD_SIZE], element)?,
+            ))
+        };
+        let x = fq2(&bytes[..2 * FIELD_SIZE])?;
+        let point = match flag {
+            UNCOMPRESSED => G2Affine::new_unchecked(x, fq2(&bytes[2 * FIELD_SIZE..])?),
+            _ => G2Affine::get_point_from_x_unchecked(x, flag == COMPRESSED_LARGEST)
+                .ok_or_else(|| malformed(self.name, format!("{} is not on the curve", element)))?,
+        };
+        let _ = check_g2(&g2_seal(&point), element)?;
+        Ok(point)
+    }
+
+    fn end(&self) -> Result<(), VerifierError> {
+        match self.bytes.is_empty() {
+            true => Ok(()),
+            false => Err(malformed(self.name, "trailing data")),
+        }
+    }
+}
+
+fn g1_seal(point: &G1Affine) -> Vec<Vec<u8>> {
+    vec![fq_bytes(&point.x), fq_bytes(&point.y)]
+}
+
+fn g2_seal(point: &G2Affine) -> Vec<Vec<Vec<u8>>> {
+    vec![
+        vec![fq_bytes(&point.x.c1), fq_bytes(&point.x.c0)],
+        vec![fq_bytes(&point.y.c1), fq_bytes(&point.y.c0)],
+    ]
+}
+
+/// Serializes the seal as a gnark groth16 `Proof`: Ar, Bs and Krs, followed by
+/// an empty list of Pedersen commitments and their proof of knowledge, the
+/// point at infinity.
+pub fn proof_bytes(seal: &Seal, encoding: GnarkEncoding) -> Result<Vec<u8>, VerifierError> {
+    let mut out = Vec::new();
+    write_g1(&mut out, &check_g1(&seal.a, "A")?, encoding);
+    write_g2(&mut out, &check_g2(&seal.b, "B")?, encoding);
+    write_g1(&mut out, &check_g1(&seal.c, "C")?, encoding);
+    write_u32(&mut out, 0);
+    write_g1(&mut out, &G1Affine::zero(), encoding);
+    Ok(out)
+}
+
+pub fn seal_from_proof_bytes(bytes: &[u8]) -> Result<Seal, VerifierError> {
+    let mut reader = Reader {
+        bytes,
+        name: "proof",
+    };
+    let a = reader.g1("A")?;
+    let b = reader.g2("B")?;
+    let c = reader.g1("C")?;
+    if reader.u32()? != 0 {
+        return Err(malformed("proof", "Pedersen commitments are not supported"));
+    }
+    let _ = reader.g1("commitment proof of knowledge")?;
+    reader.end()?;
+
+    Ok(Seal {
+        a: g1_seal(&a),
+        b: g2_seal(&b),
+        c: g1_seal(&c),
+    })
+}
+
+/// Serializes a verifying key as a gnark groth16 `VerifyingKey`. The risc0 key
+/// has no beta and delta in G1, which the gnark verifier does not use, so they
+/// are written as the point at infinity.
+pub fn verifying_key_bytes(
+    vk: &ark_groth16::VerifyingKey<Bn254>,
+    encoding: GnarkEncoding,
+) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_g1(&mut out, &vk.alpha_g1, encoding);
+    write_g1(&mut out, &G1Affine::zero(), encoding);
+    write_g2(&mut out, &vk.beta_g2, encoding);
+    write_g2(&mut out, &vk.gamma_g2, encoding);
+    write_g1(&mut out, &G1Affine::zero(), encoding);
+    write_g2(&mut out, &vk.delta_g2, encoding);
+    write_u32(&mut out, vk.gamma_abc_g1.len());
+    for point in &vk.gamma_abc_g1 {
+        write_g1(&mut out, point, encoding);
+    }
+    // No public inputs committed and no commitment keys
+    write_u32(&mut out, 0);
+    write_u32(&mut out, 0);
+    out
+}
+
+pub fn verifying_key_from_bytes(
+    bytes: &[u8],
+) -> Result<ark_groth16::VerifyingKey<Bn254>, VerifierError> {
+    let mut reader = Reader {
+        bytes,
+        name: "verifying key",
+    };
+    let alpha_g1 = reader.g1("alpha")?;
+    let _ = reader.g1("beta")?;
+    let beta_g2 = reader.g2("beta")?;
+    let gamma_g2 = reader.g2("gamma")?;
+    let _ = reader.g1("delta")?;
+    let delta_g2 = reader.g2("delta")?;
+    let gamma_abc_g1 = (0..reader.u32()?)
+        .map(|i| reader.g1(&format!("K[{}]", i)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if reader.u32()? != 0 || reader.u32()? != 0 {
+        return Err(malformed(
+            "verifying key",
+            "Pedersen commitments are not supported",
+        ));
+    }
+    reader.end()?;
+
+    Ok(ark_groth16::VerifyingKey {
+        alpha_g1,
+        beta_g2,
+        gamma_g2,
+        delta_g2,
+        gamma_abc_g1,
+    })
+}
@@ -1,19 +1,48 @@
+pub mod bundle;
+pub mod check;
 pub mod cli;
+pub mod error;
+pub mod evm;
 pub mod format;
-
-use ark_bn254::Bn254;
-use hex;
+pub mod gnark;
+pub mod merkle;
+pub mod output;
+pub mod pairing;
+pub mod signature;
+pub mod snarkjs;
+pub mod tapscript;
+pub mod winternitz;
+pub mod witness;
+
+use ark_bn254::{Bn254, G1Affine};
+use ark_ec::bn::G2Prepared;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use num_bigint::BigUint;
 use risc0_groth16::Verifier;
-use risc0_groth16::{fr_from_hex_string, split_digest, Seal};
+use risc0_groth16::{fr_from_hex_string, split_digest, Fr, Seal};
 use risc0_zkp::core::digest::Digest;
 use risc0_zkp::verify::VerificationError;
 use risc0_zkvm::sha::Digestible;
 use risc0_zkvm::{Groth16ReceiptVerifierParameters, MaybePruned, SystemState, VerifierContext};
 use sha2::{Digest as sha2digest, Sha256};
-use std::fs::{read_to_string, write};
 use std::vec;
 
+use crate::bundle::{read_bundle, write_bundle, ProofBundle};
+use crate::check::check_seal;
+use crate::error::VerifierError;
 use crate::format::*;
+use crate::gnark::GnarkEncoding;
+use crate::merkle::MerkleTree;
+use crate::output::*;
+use crate::pairing::PairingOperands;
+use crate::signature::*;
+use crate::snarkjs::*;
+use crate::winternitz::{
+    WinternitzHash, WinternitzKey, WinternitzParams, WinternitzPublicKeys, WinternitzSignature,
+    WinternitzSignatures, PROOF_SIZE,
+};
+use crate::witness::WitnessPacking;
 
 pub fn generate_proof_bytes_from_seal(seal: Seal) -> Vec<Vec<u8>> {
     let bytes_proof_a = g1_to_c_bytes(seal.a.clone());
@@ -23,10 +52,16 @@ pub fn generate_proof_bytes_from_seal(seal: Seal) -> Vec<Vec<u8>> {
     vec![bytes_proof_a, bytes_proof_b, bytes_proof_c]
 }
 
-fn show_claim(image_id: &String, journal: &Vec<u8>) {
+pub fn generate_claim(image_id: &[u32; 8], journal: &[u8]) -> Result<ClaimOutput, VerifierError> {
     let claim = get_claim(image_id, journal);
     let digest = claim.digest();
-    println!("Claim: {:?} {:?}", claim, digest);
+
+    Ok(ClaimOutput {
+        image_id: hex::encode(Digest::new(*image_id)),
+        journal: hex::encode(journal),
+        claim_digest: hex::encode(digest),
+        claim: format!("{:?} {:?}", claim, digest),
+    })
 }
 
 fn get_default_parameters() -> Result<Groth16ReceiptVerifierParameters, VerificationError> {
@@ -38,14 +73,13 @@ fn get_default_parameters() -> Result<Groth16ReceiptVerifierParameters, Verifica
     Ok(params.clone())
 }
 
-fn verify(
-    image_id: &String,
-    journal: &Vec<u8>,
-    seal_fname: &String,
-) -> Result<(), VerificationError> {
+// Public inputs as the risc0 verifier computes them, with split_digest
+fn groth16_public_inputs(
+    params: &Groth16ReceiptVerifierParameters,
+    image_id: &[u32; 8],
+    journal: &[u8],
+) -> Result<Vec<Fr>, VerifierError> {
     let claim = get_claim(image_id, journal);
-    let seal = get_seal(&seal_fname);
-    let params = get_default_parameters()?;
 
     let (a0, a1) =
         split_digest(params.control_root).map_err(|_| VerificationError::ReceiptFormatError)?;
@@ -58,13 +92,676 @@ fn verify(
     let id_bn254_fr = fr_from_hex_string(&hex::encode(id_bn254))
         .map_err(|_| VerificationError::ReceiptFormatError)?;
 
-    Verifier::new(&seal, &[a0, a1, c0, c1, id_bn254_fr], &params.verifying_key)
+    Ok(vec![a0, a1, c0, c1, id_bn254_fr])
+}
+
+fn verify_seal(image_id: &[u32; 8], journal: &[u8], seal: &Seal) -> Result<(), VerifierError> {
+    let params = get_default_parameters()?;
+    let inputs = groth16_public_inputs(&params, image_id, journal)?;
+
+    Verifier::new(seal, &inputs, &params.verifying_key)
         .map_err(|_| VerificationError::ReceiptFormatError)?
         .verify()
         .map_err(|_| VerificationError::InvalidProof)?;
 
-    println!("Verification successful");
+    Ok(())
+}
+
+pub fn verify(
+    image_id: &[u32; 8],
+    journal: &[u8],
+    seal: &Seal,
+) -> Result<VerifyOutput, VerifierError> {
+    check_proof(ProofCheck::Verify(image_id), journal, seal)?;
+
+    Ok(VerifyOutput {
+        verified: true,
+        image_id: hex::encode(Digest::new(*image_id)),
+        journal: hex::encode(journal),
+        claim_digest: hex::encode(get_claim(image_id, journal).digest()),
+    })
+}
+
+pub fn create_bundle(
+    image_id: &[u32; 8],
+    journal: &[u8],
+    seal: &Seal,
+    bundle_fname: &str,
+) -> Result<BundleOutput, VerifierError> {
+    check_proof(ProofCheck::Verify(image_id), journal, seal)?;
+
+    let bundle = ProofBundle::new(image_id, journal, seal)?;
+    write_bundle(&bundle, bundle_fname)?;
+
+    Ok(BundleOutput {
+        bundle: bundle_fname.to_string(),
+        image_id: hex::encode(Digest::new(*image_id)),
+        claim_digest: bundle.claim_digest,
+    })
+}
+
+pub fn generate_operator_key(
+    scheme: SignatureScheme,
+    key_fname: &str,
+) -> Result<KeyOutput, VerifierError> {
+    let key = generate_key(scheme);
+    write_key(&key, key_fname)?;
+
+    Ok(KeyOutput {
+        key_file: key_fname.to_string(),
+        scheme: scheme.to_string(),
+        public_key: key.public_key,
+    })
+}
+
+// The operator only signs a proof that verifies
+pub fn sign_proof_bundle(
+    bundle_fname: &str,
+    key_fname: &str,
+    output_fname: &str,
+) -> Result<SignOutput, VerifierError> {
+    let mut bundle = read_bundle(bundle_fname)?;
+    check_proof(
+        ProofCheck::Verify(&bundle.image_id),
+        &bundle.journal()?,
+        &bundle.seal()?,
+    )?;
+
+    let signature = sign_bundle(&bundle, &read_key(key_fname)?)?;
+    bundle.signature = Some(signature.clone());
+    write_bundle(&bundle, output_fname)?;
+
+    Ok(SignOutput {
+        bundle: output_fname.to_string(),
+        scheme: signature.scheme.to_string(),
+        public_key: signature.public_key,
+    })
+}
+
+// Checks the operator signature, and the operator key when one is expected,
+// before the groth16 proof
+pub fn verify_signed_bundle(
+    bundle_fname: &str,
+    operator: Option<&str>,
+) -> Result<SignedVerifyOutput, VerifierError> {
+    let bundle = read_bundle(bundle_fname)?;
+    let signature = bundle
+        .signature
+        .clone()
+        .ok_or_else(|| VerifierError::InvalidBundle("bundle is not signed".to_string()))?;
+
+    if let Some(operator) = operator {
+        if !operator.eq_ignore_ascii_case(&signature.public_key) {
+            return Err(VerifierError::InvalidBundle(format!(
+                "signed by {} instead of {}",
+                signature.public_key, operator
+            )));
+        }
+    }
+
+    let journal = bundle.journal()?;
+    check_proof(
+        ProofCheck::Verify(&bundle.image_id),
+        &journal,
+        &bundle.seal()?,
+    )?;
+
+    Ok(SignedVerifyOutput {
+        verified: true,
+        scheme: signature.scheme.to_string(),
+        public_key: signature.public_key,
+        image_id: hex::encode(Digest::new(bundle.image_id)),
+        journal: hex::encode(journal),
+        claim_digest: bundle.claim_digest,
+    })
+}
+
+pub fn generate_winternitz_key(
+    hash: WinternitzHash,
+    digit_bits: u8,
+    journal_size: usize,
+    key_fname: &str,
+    public_fname: &str,
+) -> Result<WinternitzKeyOutput, VerifierError> {
+    let key = WinternitzKey::generate(hash, digit_bits, journal_size);
+    let public_keys = key.public_keys()?;
+    write_key(&key, key_fname)?;
+    write_json(&public_keys, public_fname)?;
+
+    Ok(WinternitzKeyOutput {
+        key_file: key_fname.to_string(),
+        public_keys: public_fname.to_string(),
+        hash: hash.to_string(),
+        digit_bits,
+        journal_digits: public_keys.journal.len(),
+        proof_digits: public_keys.proof.len(),
+    })
+}
+
+// The journal and the compressed proof of proof_as_input are signed apart
+pub fn winternitz_sign(
+    key_fname: &str,
+    check: ProofCheck,
+    journal: &[u8],
+    seal: &Seal,
+    signatures_fname: &str,
+) -> Result<WinternitzSignOutput, VerifierError> {
+    check_proof(check, journal, seal)?;
+    let proof = generate_proof_bytes_from_seal(seal.clone()).concat();
+
+    let mut key: WinternitzKey = winternitz::read_json(key_fname)?;
+    let signatures = key.sign_proof(journal, &proof)?;
+    // The key is marked as used before any signature leaves it
+    update_key(&key, key_fname)?;
+    write_json(&signatures, signatures_fname)?;
+
+    Ok(WinternitzSignOutput {
+        signatures: signatures_fname.to_string(),
+        journal: hex::encode(journal),
+        proof: hex::encode(proof),
+        verified: check.verifies(),
+    })
+}
+
+// Checks both signatures and returns the signed journal and proof
+fn verify_winternitz_signatures(
+    public_keys: &WinternitzPublicKeys,
+    signatures: &WinternitzSignatures,
+) -> Result<(Vec<u8>, Vec<u8>), VerifierError> {
+    if signatures.hash != public_keys.hash || signatures.digit_bits != public_keys.digit_bits {
+        return Err(VerifierError::InvalidSignature(format!(
+            "signed with {} digits of {} bits, the public keys use {} digits of {} bits",
+            signatures.hash, signatures.digit_bits, public_keys.hash, public_keys.digit_bits
+        )));
+    }
+
+    let journal = winternitz::verify_signature(
+        "journal",
+        &public_keys.journal_params()?,
+        &public_keys.journal,
+        &signatures.journal,
+    )?;
+    let proof = winternitz::verify_signature(
+        "proof",
+        &public_keys.proof_params()?,
+        &public_keys.proof,
+        &signatures.proof,
+    )?;
+    Ok((journal, proof))
+}
+
+pub fn winternitz_verify(
+    public_fname: &str,
+    signatures_fname: &str,
+) -> Result<WinternitzVerifyOutput, VerifierError> {
+    let public_keys: WinternitzPublicKeys = winternitz::read_json(public_fname)?;
+    let signatures: WinternitzSignatures = winternitz::read_json(signatures_fname)?;
+    let (journal, proof) = verify_winternitz_signatures(&public_keys, &signatures)?;
+
+    Ok(WinternitzVerifyOutput {
+        verified: true,
+        journal: hex::encode(journal),
+        proof: hex::encode(proof),
+    })
+}
+
+fn tapscript_output(
+    params: &WinternitzParams,
+    public_key: &[String],
+    signature: Option<&WinternitzSignature>,
+) -> Result<TapscriptOutput, VerifierError> {
+    let leaf = tapscript::leaf_script(params, public_key)?;
+    let witness = signature
+        .map(|signature| tapscript::witness(params, signature))
+        .transpose()?;
+
+    Ok(TapscriptOutput {
+        fragment: hex::encode(tapscript::fragment(params, public_key)?.as_bytes()),
+        leaf: hex::encode(leaf.as_bytes()),
+        leaf_size: leaf.len(),
+        witness: witness.map(|witness| witness.iter().map(hex::encode).collect()),
+    })
+}
+
+// The witness is only built from signatures that verify
+pub fn winternitz_script(
+    public_fname: &str,
+    signatures_fname: Option<&str>,
+) -> Result<WinternitzScriptOutput, VerifierError> {
+    let public_keys: WinternitzPublicKeys = winternitz::read_json(public_fname)?;
+    let signatures = signatures_fname
+        .map(winternitz::read_json::<WinternitzSignatures>)
+        .transpose()?;
+    if let Some(signatures) = &signatures {
+        verify_winternitz_signatures(&public_keys, signatures)?;
+    }
+
+    Ok(WinternitzScriptOutput {
+        journal: tapscript_output(
+            &public_keys.journal_params()?,
+            &public_keys.journal,
+            signatures.as_ref().map(|signatures| &signatures.journal),
+        )?,
+        proof: tapscript_output(
+            &public_keys.proof_params()?,
+            &public_keys.proof,
+            signatures.as_ref().map(|signatures| &signatures.proof),
+        )?,
+    })
+}
+
+// Public inputs of the groth16 circuit: the halves of the control root, the
+// halves of the claim digest and the BN254 control id. They are split with
+// split_digest_custom, as in the template.
+fn public_inputs(image_id: &[u32; 8], journal: &[u8]) -> Result<Vec<BigUint>, VerifierError> {
+    let params = get_default_parameters()?;
+    let (a0, a1) = split_digest_custom(params.control_root);
+    let (c0, c1) = split_digest_custom(get_claim(image_id, journal).digest());
+
+    Ok(vec![
+        a0.into(),
+        a1.into(),
+        c0.into(),
+        c1.into(),
+        BigUint::from_bytes_le(params.bn254_control_id.as_bytes()),
+    ])
+}
+
+fn fr_to_biguint(fr: &Fr) -> BigUint {
+    use risc0_ark_ff::PrimeField;
+    fr.ark_fr().into_bigint().into()
+}
+
+pub fn list_public_inputs(
+    image_id: &[u32; 8],
+    journal: &[u8],
+) -> Result<PublicInputsOutput, VerifierError> {
+    let params = get_default_parameters()?;
+    let expected = groth16_public_inputs(&params, image_id, journal)?;
+    let names = [
+        "control_root_0",
+        "control_root_1",
+        "claim_digest_0",
+        "claim_digest_1",
+        "bn254_control_id",
+    ];
+
+    let mut inputs = Vec::new();
+    for ((name, value), fr) in names
+        .iter()
+        .zip(public_inputs(image_id, journal)?)
+        .zip(&expected)
+    {
+        if value != fr_to_biguint(fr) {
+            return Err(VerifierError::MalformedInput(format!(
+                "split_digest and split_digest_custom disagree on {}",
+                name
+            )));
+        }
+
+        let mut le_bytes = value.to_bytes_le();
+        le_bytes.resize(32, 0);
+        inputs.push(PublicInput {
+            name: name.to_string(),
+            decimal: value.to_string(),
+            hex: format!("{:064x}", value),
+            c_bytes: bytes_to_str(&le_bytes),
+        });
+    }
+
+    Ok(PublicInputsOutput {
+        image_id: hex::encode(Digest::new(*image_id)),
+        journal: hex::encode(journal),
+        claim_digest: hex::encode(get_claim(image_id, journal).digest()),
+        inputs,
+    })
+}
+
+pub fn export_snarkjs(
+    image_id: &[u32; 8],
+    journal: &[u8],
+    seal: &Seal,
+    skip_verify: bool,
+    proof_fname: &str,
+    public_fname: &str,
+    vkey_fname: &str,
+) -> Result<SnarkjsExportOutput, VerifierError> {
+    check_proof(ProofCheck::new(image_id, skip_verify), journal, seal)?;
+
+    let public: Vec<String> = public_inputs(image_id, journal)?
+        .iter()
+        .map(BigUint::to_string)
+        .collect();
+    let vkey = SnarkjsVerifyingKey::new(&get_verifying_key_clone(&get_default_parameters()?));
+
+    write_json(&proof_from_seal(seal), proof_fname)?;
+    write_json(&public, public_fname)?;
+    write_json(&vkey, vkey_fname)?;
+
+    Ok(SnarkjsExportOutput {
+        proof: proof_fname.to_string(),
+        public: public_fname.to_string(),
+        verification_key: vkey_fname.to_string(),
+        public_inputs: public,
+        verified: !skip_verify,
+    })
+}
+
+// The public inputs and the verifying key are optional, when given they must
+// be the ones of the claim and of the risc0 verifier
+pub fn import_snarkjs(
+    image_id: &[u32; 8],
+    journal: &[u8],
+    proof_fname: &str,
+    public_fname: Option<&str>,
+    vkey_fname: Option<&str>,
+    seal_fname: &str,
+) -> Result<ImportOutput, VerifierError> {
+    let seal = seal_from_proof(&read_json(proof_fname)?)?;
+
+    if let Some(public_fname) = public_fname {
+        let public: Vec<String> = read_json(public_fname)?;
+        let expected: Vec<String> = public_inputs(image_id, journal)?
+            .iter()
+            .map(BigUint::to_string)
+            .collect();
+        if public != expected {
+            return Err(VerifierError::MalformedInput(format!(
+                "Public inputs of {} are not the ones of the claim: {:?}",
+                public_fname, expected
+            )));
+        }
+    }
+
+    if let Some(vkey_fname) = vkey_fname {
+        let vkey: SnarkjsVerifyingKey = read_json(vkey_fname)?;
+        let expected =
+            SnarkjsVerifyingKey::new(&get_verifying_key_clone(&get_default_parameters()?));
+        if !vkey.same_key(&expected) {
+            return Err(VerifierError::MalformedInput(format!(
+                "{} is not the risc0 verifying key",
+                vkey_fname
+            )));
+        }
+    }
+
+    check_proof(ProofCheck::Verify(image_id), journal, &seal)?;
+    write_seal(&seal, journal, seal_fname)?;
+
+    Ok(ImportOutput {
+        seal: seal_fname.to_string(),
+        verified: true,
+        claim_digest: Some(hex::encode(get_claim(image_id, journal).digest())),
+    })
+}
+
+// The journal and the compressed proof of proof_as_input are chunked apart,
+// so that no element holds bytes of both
+pub fn export_bitcoin_witness(
+    check: ProofCheck,
+    journal: &[u8],
+    seal: &Seal,
+    packing: WitnessPacking,
+    chunk_size: usize,
+) -> Result<WitnessOutput, VerifierError> {
+    check_proof(check, journal, seal)?;
+    let proof = generate_proof_bytes_from_seal(seal.clone()).concat();
+    let witness = witness::witness(journal, &proof, packing, chunk_size)?;
+
+    Ok(WitnessOutput {
+        packing,
+        chunk_size,
+        journal_elements: witness::pack(journal, packing, chunk_size)?.len(),
+        proof_elements: witness::pack(&proof, packing, chunk_size)?.len(),
+        standard: witness::is_standard(&witness),
+        witness: witness.iter().map(hex::encode).collect(),
+        verified: check.verifies(),
+    })
+}
+
+pub fn import_bitcoin_witness(
+    check: ProofCheck,
+    witness_fname: &str,
+    packing: WitnessPacking,
+    seal_fname: &str,
+) -> Result<WitnessImportOutput, VerifierError> {
+    let witness = witness::read_witness(&read_file_to_string(witness_fname)?)?;
+    let (journal, proof) = witness::parse_witness(&witness, packing, PROOF_SIZE)?;
+    let seal = seal_from_c_bytes(&proof)?;
+
+    check_proof(check, &journal, &seal)?;
+    write_seal(&seal, &journal, seal_fname)?;
+
+    Ok(WitnessImportOutput {
+        seal: seal_fname.to_string(),
+        journal: hex::encode(&journal),
+        verified: check.verifies(),
+        claim_digest: check
+            .image_id()
+            .map(|image_id| hex::encode(get_claim(image_id, &journal).digest())),
+    })
+}
+
+pub fn evm_seal(
+    image_id: &[u32; 8],
+    journal: &[u8],
+    seal: &Seal,
+    skip_verify: bool,
+) -> Result<EvmSealOutput, VerifierError> {
+    check_proof(ProofCheck::new(image_id, skip_verify), journal, seal)?;
+
+    let params = get_default_parameters()?;
+    let image_id = Digest::new(*image_id);
+    let journal_digest = journal.digest();
+    let seal = evm::encode_seal(&params, seal);
+    let arguments = evm::encode_verify_arguments(&seal, &image_id, &journal_digest);
+
+    Ok(EvmSealOutput {
+        selector: format!("0x{}", hex::encode(evm::selector(&params))),
+        seal: format!("0x{}", hex::encode(seal)),
+        image_id: format!("0x{}", hex::encode(image_id)),
+        journal_digest: format!("0x{}", hex::encode(journal_digest)),
+        verify_arguments: format!("0x{}", hex::encode(arguments)),
+        verified: !skip_verify,
+    })
+}
+
+// The tree is built over the bytes of proof_as_input, the journal followed by
+// the compressed proof
+pub fn merkle_commit(
+    check: ProofCheck,
+    journal: &[u8],
+    seal: &Seal,
+    word_size: usize,
+) -> Result<MerkleOutput, VerifierError> {
+    check_proof(check, journal, seal)?;
+    let input = [
+        journal.to_vec(),
+        generate_proof_bytes_from_seal(seal.clone()).concat(),
+    ]
+    .concat();
+    let tree = MerkleTree::new(&input, word_size)?;
+
+    let leaves = tree
+        .words()
+        .iter()
+        .enumerate()
+        .map(|(index, word)| {
+            Ok(MerkleLeaf {
+                index,
+                word: hex::encode(word),
+                proof: tree.proof(index)?.iter().map(hex::encode).collect(),
+            })
+        })
+        .collect::<Result<_, VerifierError>>()?;
+
+    Ok(MerkleOutput {
+        input: hex::encode(&input),
+        word_size,
+        depth: tree.depth(),
+        root: hex::encode(tree.root()),
+        leaves,
+        verified: check.verifies(),
+    })
+}
+
+fn merkle_hash(hash: &str) -> Result<merkle::Hash, VerifierError> {
+    hex::decode(hash)
+        .ok()
+        .and_then(|hash| hash.try_into().ok())
+        .ok_or_else(|| VerifierError::MalformedInput(format!("Invalid SHA-256 hash {}", hash)))
+}
+
+pub fn merkle_check(
+    root: &str,
+    index: usize,
+    word: &str,
+    proof: &[String],
+) -> Result<MerkleCheckOutput, VerifierError> {
+    let root_hash = merkle_hash(root)?;
+    let word_bytes = hex::decode(word)
+        .map_err(|e| VerifierError::MalformedInput(format!("Invalid word {}: {}", word, e)))?;
+    let proof = proof
+        .iter()
+        .map(|sibling| merkle_hash(sibling))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if !merkle::verify_proof(&root_hash, index, &word_bytes, &proof) {
+        return Err(VerifierError::InvalidMerkleProof(format!(
+            "{} is not the leaf {} of {}",
+            word, index, root
+        )));
+    }
+
+    Ok(MerkleCheckOutput {
+        root: root.to_string(),
+        index,
+        word: word.to_string(),
+        valid: true,
+    })
+}
+
+pub fn export_pairings(
+    image_id: &[u32; 8],
+    journal: &[u8],
+    seal: &Seal,
+    skip_verify: bool,
+) -> Result<PairingsOutput, VerifierError> {
+    check_proof(ProofCheck::new(image_id, skip_verify), journal, seal)?;
+
+    let vk = get_verifying_key_clone(&get_default_parameters()?);
+    let inputs: Vec<ark_bn254::Fr> = public_inputs(image_id, journal)?
+        .into_iter()
+        .map(ark_bn254::Fr::from)
+        .collect();
+    let operands = PairingOperands::new(&vk, seal, &inputs)?;
+
+    let g1 = |point: &G1Affine| hex::encode(g1_affine_to_c_bytes(point));
+    let g2 = |point: &ark_bn254::G2Affine| hex::encode(g2_affine_to_c_bytes(point));
+    Ok(PairingsOutput {
+        neg_a: g1(&operands.neg_a),
+        b: g2(&operands.b),
+        alpha: g1(&operands.alpha),
+        beta: g2(&operands.beta),
+        l: g1(&operands.l),
+        gamma: g2(&operands.gamma),
+        c: g1(&operands.c),
+        delta: g2(&operands.delta),
+        product_is_one: operands.check(),
+        verified: !skip_verify,
+    })
+}
+
+pub fn export_gnark(
+    check: ProofCheck,
+    journal: &[u8],
+    seal: &Seal,
+    encoding: GnarkEncoding,
+    proof_fname: &str,
+    vkey_fname: &str,
+) -> Result<GnarkExportOutput, VerifierError> {
+    check_proof(check, journal, seal)?;
+    let vk = get_verifying_key_clone(&get_default_parameters()?);
+
+    write_file(proof_fname, gnark::proof_bytes(seal, encoding)?)?;
+    write_file(vkey_fname, gnark::verifying_key_bytes(&vk, encoding))?;
+
+    Ok(GnarkExportOutput {
+        proof: proof_fname.to_string(),
+        verification_key: vkey_fname.to_string(),
+        encoding,
+        verified: check.verifies(),
+    })
+}
+
+// Either encoding is accepted, the flag of every point tells how it is written
+pub fn import_gnark(
+    check: ProofCheck,
+    journal: &[u8],
+    proof_fname: &str,
+    vkey_fname: Option<&str>,
+    seal_fname: &str,
+) -> Result<ImportOutput, VerifierError> {
+    let seal = gnark::seal_from_proof_bytes(&read_file(proof_fname)?)?;
+
+    if let Some(vkey_fname) = vkey_fname {
+        let vk = gnark::verifying_key_from_bytes(&read_file(vkey_fname)?)?;
+        if vk != get_verifying_key_clone(&get_default_parameters()?) {
+            return Err(VerifierError::MalformedInput(format!(
+                "{} is not the risc0 verifying key",
+                vkey_fname
+            )));
+        }
+    }
+
+    check_proof(check, journal, &seal)?;
+    write_seal(&seal, journal, seal_fname)?;
+
+    Ok(ImportOutput {
+        seal: seal_fname.to_string(),
+        verified: check.verifies(),
+        claim_digest: check
+            .image_id()
+            .map(|image_id| hex::encode(get_claim(image_id, journal).digest())),
+    })
+}
+
+/// Whether a command verifies the proof against its claim before using it. The
+/// seal points are checked either way.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ProofCheck<'a> {
+    /// Verify against the claim of this image id and the journal
+    Verify(&'a [u32; 8]),
+    /// Only check the seal points, for proofs that do not verify yet
+    SkipVerify,
+}
+
+impl<'a> ProofCheck<'a> {
+    pub fn new(image_id: &'a [u32; 8], skip_verify: bool) -> Self {
+        match skip_verify {
+            true => ProofCheck::SkipVerify,
+            false => ProofCheck::Verify(image_id),
+        }
+    }
 
+    pub fn image_id(&self) -> Option<&'a [u32; 8]> {
+        match self {
+            ProofCheck::Verify(image_id) => Some(image_id),
+            ProofCheck::SkipVerify => None,
+        }
+    }
+
+    pub fn verifies(&self) -> bool {
+        matches!(self, ProofCheck::Verify(_))
+    }
+}
+
+// Checks the seal before it is written anywhere
+fn check_proof(check: ProofCheck, journal: &[u8], seal: &Seal) -> Result<(), VerifierError> {
+    check_seal(seal)?;
+    if let ProofCheck::Verify(image_id) = check {
+        verify_seal(image_id, journal, seal)?;
+    }
     Ok(())
 }
 
@@ -90,18 +787,35 @@ fn get_verifying_key_clone(
     }
 }
 
+// gamma_abc[0] plus the terms of the inputs that are fixed for a risc0 version:
+// the halves of the control root and the BN254 control id. The C verifier only
+// has to add the terms of the two claim digest halves.
+fn fixed_inputs_msm(
+    params: &Groth16ReceiptVerifierParameters,
+    vk: &ark_groth16::VerifyingKey<Bn254>,
+) -> G1Affine {
+    let (a0, a1) = split_digest_custom(params.control_root);
+    let id = ark_bn254::Fr::from(BigUint::from_bytes_le(params.bn254_control_id.as_bytes()));
+
+    (vk.gamma_abc_g1[0].into_group()
+        + vk.gamma_abc_g1[1] * ark_bn254::Fr::from(a0)
+        + vk.gamma_abc_g1[2] * ark_bn254::Fr::from(a1)
+        + vk.gamma_abc_g1[5] * id)
+        .into_affine()
+}
+
 pub fn template_setup(
-    image_id_fname: &String,
-    template_fname: &String,
-    output_fname: &String,
+    image_id: &[u32; 8],
+    template_fname: &str,
+    output_fname: &str,
     zero_proof: bool,
-) {
-    let mut template = read_to_string(template_fname).unwrap();
+    prepared_lines: bool,
+) -> Result<TemplateOutput, VerifierError> {
+    let mut template = read_file_to_string(template_fname)?;
 
-    let image_id = get_image_id(image_id_fname);
-    let claim_pre = Digest::new(image_id);
+    let claim_pre = Digest::new(*image_id);
 
-    let params = get_default_parameters().unwrap();
+    let params = get_default_parameters()?;
     let root_id = params.control_root;
     let (a0, a1) = split_digest_custom(root_id);
 
@@ -132,7 +846,7 @@ pub fn template_setup(
         merkle_root: Digest::ZERO,
     })
     .digest();
-    template = template.replace("claim_post", &bytes_to_str(&claim_post.as_bytes()));
+    template = template.replace("claim_post", &bytes_to_str(claim_post.as_bytes()));
 
     let vk = get_verifying_key_clone(&params);
     template = template.replace(
@@ -140,75 +854,99 @@ pub fn template_setup(
         &bytes_to_str(&g1_to_c_bytes(g1_strings_to_vec(split_g1(format!(
             "{:?}",
             vk.alpha_g1
-        ))))),
+        )))?)),
     );
     template = template.replace(
         "vk_beta_g2",
         &bytes_to_str(&g2_to_c_bytes(g2_strings_to_vec(split_g2(format!(
             "{:?}",
             vk.beta_g2
-        ))))),
+        )))?)),
     );
     template = template.replace(
         "vk_gamma_g2",
         &bytes_to_str(&g2_to_c_bytes(g2_strings_to_vec(split_g2(format!(
             "{:?}",
             vk.gamma_g2
-        ))))),
+        )))?)),
     );
     template = template.replace(
         "vk_delta_g2",
         &bytes_to_str(&g2_to_c_bytes(g2_strings_to_vec(split_g2(format!(
             "{:?}",
             vk.delta_g2
-        ))))),
+        )))?)),
     );
     template = template.replace(
         "vk_gamma_abc_0",
         &bytes_to_str(&g1_to_c_bytes(g1_strings_to_vec(split_g1(format!(
             "{:?}",
             vk.gamma_abc_g1[0]
-        ))))),
+        )))?)),
     );
     template = template.replace(
         "vk_gamma_abc_1",
         &bytes_to_str(&g1_to_c_bytes(g1_strings_to_vec(split_g1(format!(
             "{:?}",
             vk.gamma_abc_g1[1]
-        ))))),
+        )))?)),
     );
     template = template.replace(
         "vk_gamma_abc_2",
         &bytes_to_str(&g1_to_c_bytes(g1_strings_to_vec(split_g1(format!(
             "{:?}",
             vk.gamma_abc_g1[2]
-        ))))),
+        )))?)),
     );
     template = template.replace(
         "vk_gamma_abc_3",
         &bytes_to_str(&g1_to_c_bytes(g1_strings_to_vec(split_g1(format!(
             "{:?}",
             vk.gamma_abc_g1[3]
-        ))))),
+        )))?)),
     );
     template = template.replace(
         "vk_gamma_abc_4",
         &bytes_to_str(&g1_to_c_bytes(g1_strings_to_vec(split_g1(format!(
             "{:?}",
             vk.gamma_abc_g1[4]
-        ))))),
+        )))?)),
     );
     template = template.replace(
         "vk_gamma_abc_5",
         &bytes_to_str(&g1_to_c_bytes(g1_strings_to_vec(split_g1(format!(
             "{:?}",
             vk.gamma_abc_g1[5]
-        ))))),
+        )))?)),
+    );
+
+    // Fixed for the verifying key, so the C verifier needs no Miller loop for it
+    template = template.replace(
+        "vk_alphabeta_12",
+        &bytes_to_str(&fq12_to_c_bytes(&Bn254::pairing(vk.alpha_g1, vk.beta_g2).0)),
+    );
+    template = template.replace(
+        "vk_fixed_msm",
+        &bytes_to_str(&g1_affine_to_c_bytes(&fixed_inputs_msm(&params, &vk))),
     );
 
+    if prepared_lines {
+        let gamma = G2Prepared::from(vk.gamma_g2);
+        let delta = G2Prepared::from(vk.delta_g2);
+        template = template.replace("g2_lines_count", &gamma.ell_coeffs.len().to_string());
+        template = template.replace(
+            "gamma_g2_lines",
+            &bytes_to_str(&g2_lines_to_c_bytes(&gamma)),
+        );
+        template = template.replace(
+            "delta_g2_lines",
+            &bytes_to_str(&g2_lines_to_c_bytes(&delta)),
+        );
+    }
+
     //only variable part, the rest could be hardcoded
     //interesting to keep it in this way as claim generation could change over time (risc0 versioning)
-    template = template.replace("claim_pre", &bytes_to_str(&claim_pre.as_bytes()));
+    template = template.replace("claim_pre", &bytes_to_str(claim_pre.as_bytes()));
 
     if zero_proof {
         template = template.replace("journalx", &bytes_to_str(vec![0u8; 4].as_slice()));
@@ -217,37 +955,56 @@ pub fn template_setup(
         template = template.replace("proof_c", &bytes_to_str(vec![0u8; 32].as_slice()));
     }
 
-    write(output_fname, template).unwrap();
+    write_file(output_fname, template)?;
+
+    Ok(TemplateOutput {
+        template: template_fname.to_string(),
+        output: output_fname.to_string(),
+        verified: false,
+    })
 }
 
 pub fn template_proof(
-    journal: &Vec<u8>,
-    seal: &String,
-    template_fname: &String,
-    output_fname: &String,
-) {
-    let mut template = read_to_string(template_fname).unwrap();
+    check: ProofCheck,
+    journal: &[u8],
+    seal: &Seal,
+    template_fname: &str,
+    output_fname: &str,
+) -> Result<TemplateOutput, VerifierError> {
+    let mut template = read_file_to_string(template_fname)?;
 
-    let seal = get_seal(seal);
-    let proofs = generate_proof_bytes_from_seal(seal);
+    check_proof(check, journal, seal)?;
+    let proofs = generate_proof_bytes_from_seal(seal.clone());
 
     template = template.replace("proof_a", &bytes_to_str(&proofs[0]));
     template = template.replace("proof_b", &bytes_to_str(&proofs[1]));
     template = template.replace("proof_c", &bytes_to_str(&proofs[2]));
-    template = template.replace("journalx", &bytes_to_str(&journal));
+    template = template.replace("journalx", &bytes_to_str(journal));
+
+    write_file(output_fname, template)?;
 
-    write(output_fname, template).unwrap();
+    Ok(TemplateOutput {
+        template: template_fname.to_string(),
+        output: output_fname.to_string(),
+        verified: check.verifies(),
+    })
 }
 
-pub fn proof_as_input(journal: &Vec<u8>, seal: &String) {
-    let seal = get_seal(seal);
-    let proofs = generate_proof_bytes_from_seal(seal);
+pub fn proof_as_input(
+    check: ProofCheck,
+    journal: &[u8],
+    seal: &Seal,
+) -> Result<ProofInputOutput, VerifierError> {
+    check_proof(check, journal, seal)?;
+    let proofs = generate_proof_bytes_from_seal(seal.clone());
 
     //hex encode journal and proofs
     let journal_hex = hex::encode(journal);
-    print!("input: {}", journal_hex);
-    for proof in proofs {
-        let proof_hex = hex::encode(proof);
-        print!("{}", proof_hex);
-    }
+    let proof_hex = hex::encode(proofs.concat());
+
+    Ok(ProofInputOutput {
+        input: format!("{}{}", journal_hex, proof_hex),
+        journal: journal_hex,
+        proof: proof_hex,
+    })
 }
@@ -1,6 +1,13 @@
-use risc0_zkp::verify::VerificationError;
+use std::process::ExitCode;
+
 use verifier::cli;
 
-fn main() -> Result<(), VerificationError> {
-    cli::run()
+fn main() -> ExitCode {
+    match cli::run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
 }
@@ -0,0 +1,121 @@
+use sha2::{Digest, Sha256};
+
+use crate::error::VerifierError;
+
+pub type Hash = [u8; 32];
+
+// Leaves and nodes are hashed with different prefixes, as in RFC 6962, so
+// that a node can not be passed off as a leaf
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// Node that pads the leaves to a power of two. No word hashes to it.
+pub const EMPTY_LEAF: Hash = [0; 32];
+
+pub fn leaf_hash(word: &[u8]) -> Hash {
+    Sha256::new()
+        .chain_update([LEAF_PREFIX])
+        .chain_update(word)
+        .finalize()
+        .into()
+}
+
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    Sha256::new()
+        .chain_update([NODE_PREFIX])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// SHA-256 Merkle tree over data split in words of `word_size` bytes, the last
+/// word padded with zeros. The leaves are padded with `EMPTY_LEAF` up to a
+/// power of two, so every inclusion proof has `depth` siblings.
+pub struct MerkleTree {
+    words: Vec<Vec<u8>>,
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn new(data: &[u8], word_size: usize) -> Result<Self, VerifierError> {
+        if word_size == 0 {
+            return Err(VerifierError::MalformedInput(
+                "Merkle words have at least one byte".to_string(),
+            ));
+        }
+        if data.is_empty() {
+            return Err(VerifierError::MalformedInput(
+                "No data to build the Merkle tree over".to_string(),
+            ));
+        }
+
+        let words: Vec<Vec<u8>> = data
+            .chunks(word_size)
+            .map(|word| {
+                let mut word = word.to_vec();
+                word.resize(word_size, 0);
+                word
+            })
+            .collect();
+
+        let mut leaves: Vec<Hash> = words.iter().map(|word| leaf_hash(word)).collect();
+        leaves.resize(words.len().next_power_of_two(), EMPTY_LEAF);
+        let mut levels = vec![leaves];
+        while levels.last().expect("the leaves are a level").len() > 1 {
+            let level = levels.last().expect("the leaves are a level");
+            let parents = level
+                .chunks(2)
+                .map(|pair| node_hash(&pair[0], &pair[1]))
+                .collect();
+            levels.push(parents);
+        }
+
+        Ok(MerkleTree { words, levels })
+    }
+
+    pub fn root(&self) -> Hash {
+        self.levels.last().expect("the leaves are a level")[0]
+    }
+
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    pub fn words(&self) -> &[Vec<u8>] {
+        &self.words
+    }
+
+    /// Siblings of the path from the leaf to the root, the one of the leaf first
+    pub fn proof(&self, index: usize) -> Result<Vec<Hash>, VerifierError> {
+        if index >= self.words.len() {
+            return Err(VerifierError::MalformedInput(format!(
+                "The Merkle tree has {} leaves, no leaf {}",
+                self.words.len(),
+                index
+            )));
+        }
+        Ok(self.levels[..self.depth()]
+            .iter()
+            .enumerate()
+            .map(|(height, level)| level[(index >> height) ^ 1])
+            .collect())
+    }
+}
+
+/// Whether the word is the leaf at `index` of the tree with the given root.
+/// The bits of the index, least significant first, tell whether each node of
+/// the path is a right child.
+pub fn verify_proof(root: &Hash, index: usize, word: &[u8], proof: &[Hash]) -> bool {
+    if proof.len() < usize::BITS as usize && index >> proof.len() != 0 {
+        return false;
+    }
+    let mut node = leaf_hash(word);
+    for (height, sibling) in proof.iter().enumerate() {
+        node = match (index >> height) & 1 {
+            0 => node_hash(&node, sibling),
+            _ => node_hash(sibling, &node),
+        };
+    }
+    node == *root
+}
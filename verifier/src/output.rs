@@ -0,0 +1,446 @@
+use serde::Serialize;
+
+use crate::error::VerifierError;
+use crate::format::write_file;
+use crate::gnark::GnarkEncoding;
+use crate::witness::WitnessPacking;
+
+/// Result of a verifier command, printed either as text or as JSON
+pub trait CommandOutput: Serialize {
+    fn to_text(&self) -> String;
+}
+
+#[derive(Serialize)]
+pub struct ClaimOutput {
+    pub image_id: String,
+    pub journal: String,
+    pub claim_digest: String,
+    #[serde(skip)]
+    pub claim: String,
+}
+
+impl CommandOutput for ClaimOutput {
+    fn to_text(&self) -> String {
+        format!("Claim: {}", self.claim)
+    }
+}
+
+#[derive(Serialize)]
+pub struct VerifyOutput {
+    pub verified: bool,
+    pub image_id: String,
+    pub journal: String,
+    pub claim_digest: String,
+}
+
+impl CommandOutput for VerifyOutput {
+    fn to_text(&self) -> String {
+        "Verification successful".to_string()
+    }
+}
+
+#[derive(Serialize)]
+pub struct TemplateOutput {
+    pub template: String,
+    pub output: String,
+    pub verified: bool,
+}
+
+impl CommandOutput for TemplateOutput {
+    fn to_text(&self) -> String {
+        format!("Template written to {}", self.output)
+    }
+}
+
+#[derive(Serialize)]
+pub struct ProofInputOutput {
+    pub journal: String,
+    pub proof: String,
+    pub input: String,
+}
+
+impl CommandOutput for ProofInputOutput {
+    fn to_text(&self) -> String {
+        format!("input: {}", self.input)
+    }
+}
+
+#[derive(Serialize)]
+pub struct BundleOutput {
+    pub bundle: String,
+    pub image_id: String,
+    pub claim_digest: String,
+}
+
+impl CommandOutput for BundleOutput {
+    fn to_text(&self) -> String {
+        format!("Bundle written to {}", self.bundle)
+    }
+}
+
+#[derive(Serialize)]
+pub struct KeyOutput {
+    pub key_file: String,
+    pub scheme: String,
+    pub public_key: String,
+}
+
+impl CommandOutput for KeyOutput {
+    fn to_text(&self) -> String {
+        format!("Public key: {}", self.public_key)
+    }
+}
+
+#[derive(Serialize)]
+pub struct SignOutput {
+    pub bundle: String,
+    pub scheme: String,
+    pub public_key: String,
+}
+
+impl CommandOutput for SignOutput {
+    fn to_text(&self) -> String {
+        format!("Signed bundle written to {}", self.bundle)
+    }
+}
+
+#[derive(Serialize)]
+pub struct SignedVerifyOutput {
+    pub verified: bool,
+    pub scheme: String,
+    pub public_key: String,
+    pub image_id: String,
+    pub journal: String,
+    pub claim_digest: String,
+}
+
+impl CommandOutput for SignedVerifyOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "Verification successful, signed by {} key {}",
+            self.scheme, self.public_key
+        )
+    }
+}
+
+#[derive(Serialize)]
+pub struct WinternitzKeyOutput {
+    pub key_file: String,
+    pub public_keys: String,
+    pub hash: String,
+    pub digit_bits: u8,
+    pub journal_digits: usize,
+    pub proof_digits: usize,
+}
+
+impl CommandOutput for WinternitzKeyOutput {
+    fn to_text(&self) -> String {
+        format!("Public keys written to {}", self.public_keys)
+    }
+}
+
+#[derive(Serialize)]
+pub struct WinternitzSignOutput {
+    pub signatures: String,
+    pub journal: String,
+    pub proof: String,
+    pub verified: bool,
+}
+
+impl CommandOutput for WinternitzSignOutput {
+    fn to_text(&self) -> String {
+        format!("Signatures written to {}", self.signatures)
+    }
+}
+
+#[derive(Serialize)]
+pub struct WinternitzVerifyOutput {
+    pub verified: bool,
+    pub journal: String,
+    pub proof: String,
+}
+
+impl CommandOutput for WinternitzVerifyOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "Verification successful\njournal: {}\nproof: {}",
+            self.journal, self.proof
+        )
+    }
+}
+
+/// Tapscript that checks the Winternitz signature of a message, hex encoded.
+/// The witness elements are listed bottom first.
+#[derive(Serialize)]
+pub struct TapscriptOutput {
+    pub fragment: String,
+    pub leaf: String,
+    pub leaf_size: usize,
+    pub witness: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+pub struct WinternitzScriptOutput {
+    pub journal: TapscriptOutput,
+    pub proof: TapscriptOutput,
+}
+
+impl CommandOutput for WinternitzScriptOutput {
+    fn to_text(&self) -> String {
+        let text = |name: &str, script: &TapscriptOutput| {
+            let witness = match &script.witness {
+                Some(witness) => format!("\n{} witness: {}", name, witness.join(" ")),
+                None => String::new(),
+            };
+            format!("{} leaf: {}{}", name, script.leaf, witness)
+        };
+        format!(
+            "{}\n{}",
+            text("journal", &self.journal),
+            text("proof", &self.proof)
+        )
+    }
+}
+
+#[derive(Serialize)]
+pub struct WitnessOutput {
+    pub packing: WitnessPacking,
+    pub chunk_size: usize,
+    pub journal_elements: usize,
+    pub proof_elements: usize,
+    pub standard: bool,
+    pub witness: Vec<String>,
+    pub verified: bool,
+}
+
+impl CommandOutput for WitnessOutput {
+    fn to_text(&self) -> String {
+        format!("witness: {}", self.witness.join(" "))
+    }
+}
+
+#[derive(Serialize)]
+pub struct WitnessImportOutput {
+    pub seal: String,
+    pub journal: String,
+    pub verified: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claim_digest: Option<String>,
+}
+
+impl CommandOutput for WitnessImportOutput {
+    fn to_text(&self) -> String {
+        match self.verified {
+            true => format!(
+                "Verified seal written to {}\njournal: {}",
+                self.seal, self.journal
+            ),
+            false => format!("Seal written to {}\njournal: {}", self.seal, self.journal),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct MerkleLeaf {
+    pub index: usize,
+    pub word: String,
+    pub proof: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct MerkleOutput {
+    pub input: String,
+    pub word_size: usize,
+    pub depth: usize,
+    pub root: String,
+    pub leaves: Vec<MerkleLeaf>,
+    pub verified: bool,
+}
+
+impl CommandOutput for MerkleOutput {
+    fn to_text(&self) -> String {
+        let mut text = format!("root: {}", self.root);
+        for leaf in &self.leaves {
+            text.push_str(&format!(
+                "\nleaf {}: {} proof: {}",
+                leaf.index,
+                leaf.word,
+                leaf.proof.join(",")
+            ));
+        }
+        text
+    }
+}
+
+#[derive(Serialize)]
+pub struct MerkleCheckOutput {
+    pub root: String,
+    pub index: usize,
+    pub word: String,
+    pub valid: bool,
+}
+
+impl CommandOutput for MerkleCheckOutput {
+    fn to_text(&self) -> String {
+        format!("{} is the leaf {} of {}", self.word, self.index, self.root)
+    }
+}
+
+#[derive(Serialize)]
+pub struct SnarkjsExportOutput {
+    pub proof: String,
+    pub public: String,
+    pub verification_key: String,
+    pub public_inputs: Vec<String>,
+    pub verified: bool,
+}
+
+impl CommandOutput for SnarkjsExportOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "snarkjs files written to {}, {} and {}",
+            self.proof, self.public, self.verification_key
+        )
+    }
+}
+
+#[derive(Serialize)]
+pub struct ImportOutput {
+    pub seal: String,
+    pub verified: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claim_digest: Option<String>,
+}
+
+impl CommandOutput for ImportOutput {
+    fn to_text(&self) -> String {
+        match self.verified {
+            true => format!("Verified seal written to {}", self.seal),
+            false => format!("Seal written to {}", self.seal),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct GnarkExportOutput {
+    pub proof: String,
+    pub verification_key: String,
+    pub encoding: GnarkEncoding,
+    pub verified: bool,
+}
+
+impl CommandOutput for GnarkExportOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "gnark files written to {} and {}",
+            self.proof, self.verification_key
+        )
+    }
+}
+
+/// EVM encodings, 0x prefixed as Solidity tooling expects
+#[derive(Serialize)]
+pub struct EvmSealOutput {
+    pub selector: String,
+    pub seal: String,
+    pub image_id: String,
+    pub journal_digest: String,
+    pub verify_arguments: String,
+    pub verified: bool,
+}
+
+impl CommandOutput for EvmSealOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "seal: {}\nimage_id: {}\njournal_digest: {}",
+            self.seal, self.image_id, self.journal_digest
+        )
+    }
+}
+
+/// Operands of `e(-A, B) · e(alpha, beta) · e(L, gamma) · e(C, delta) = 1`,
+/// hex encoded in the C layout
+#[derive(Serialize)]
+pub struct PairingsOutput {
+    pub neg_a: String,
+    pub b: String,
+    pub alpha: String,
+    pub beta: String,
+    pub l: String,
+    pub gamma: String,
+    pub c: String,
+    pub delta: String,
+    pub product_is_one: bool,
+    pub verified: bool,
+}
+
+impl CommandOutput for PairingsOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "e(-A, B): {}, {}\ne(alpha, beta): {}, {}\ne(L, gamma): {}, {}\ne(C, delta): {}, {}\nproduct is one: {}",
+            self.neg_a,
+            self.b,
+            self.alpha,
+            self.beta,
+            self.l,
+            self.gamma,
+            self.c,
+            self.delta,
+            self.product_is_one
+        )
+    }
+}
+
+/// Field element of the groth16 public inputs: `hex` is big endian and
+/// `c_bytes` the 32 little endian bytes the C verifier reads
+#[derive(Serialize)]
+pub struct PublicInput {
+    pub name: String,
+    pub decimal: String,
+    pub hex: String,
+    pub c_bytes: String,
+}
+
+#[derive(Serialize)]
+pub struct PublicInputsOutput {
+    pub image_id: String,
+    pub journal: String,
+    pub claim_digest: String,
+    pub inputs: Vec<PublicInput>,
+}
+
+impl CommandOutput for PublicInputsOutput {
+    fn to_text(&self) -> String {
+        self.inputs
+            .iter()
+            .map(|input| {
+                format!(
+                    "{}:\n  decimal: {}\n  hex: {}\n  c: {}",
+                    input.name, input.decimal, input.hex, input.c_bytes
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Writes the command output to the given file, or to stdout when there is none
+pub fn emit<T: CommandOutput>(
+    output: &T,
+    json: bool,
+    file: Option<&String>,
+) -> Result<(), VerifierError> {
+    let text = match json {
+        true => serde_json::to_string_pretty(output)
+            .map_err(|e| VerifierError::Io(format!("Failed to serialize output: {}", e)))?,
+        false => output.to_text(),
+    };
+
+    match file {
+        Some(file) => write_file(file, text + "\n"),
+        None => {
+            println!("{}", text);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::One;
+use risc0_groth16::Seal;
+
+use crate::check::{check_g1, check_g2};
+use crate::error::VerifierError;
+
+/// Operands of the groth16 equation rewritten as a product of pairings,
+/// `e(-A, B) · e(alpha, beta) · e(L, gamma) · e(C, delta) = 1`, where `L` is
+/// the sum of the public input terms
+pub struct PairingOperands {
+    pub neg_a: G1Affine,
+    pub b: G2Affine,
+    pub alpha: G1Affine,
+    pub beta: G2Affine,
+    pub l: G1Affine,
+    pub gamma: G2Affine,
+    pub c: G1Affine,
+    pub delta: G2Affine,
+}
+
+impl PairingOperands {
+    pub fn new(
+        vk: &ark_groth16::VerifyingKey<Bn254>,
+        seal: &Seal,
+        inputs: &[Fr],
+    ) -> Result<Self, VerifierError> {
+        if inputs.len() + 1 != vk.gamma_abc_g1.len() {
+            return Err(VerifierError::MalformedInput(format!(
+                "The verifying key takes {} public inputs, got {}",
+                vk.gamma_abc_g1.len() - 1,
+                inputs.len()
+            )));
+        }
+
+        let l = vk.gamma_abc_g1[1..]
+            .iter()
+            .zip(inputs)
+            .fold(vk.gamma_abc_g1[0].into_group(), |sum, (point, input)| {
+                sum + *point * input
+            });
+
+        Ok(PairingOperands {
+            neg_a: -check_g1(&seal.a, "A")?,
+            b: check_g2(&seal.b, "B")?,
+            alpha: vk.alpha_g1,
+            beta: vk.beta_g2,
+            l: l.into_affine(),
+            gamma: vk.gamma_g2,
+            c: check_g1(&seal.c, "C")?,
+            delta: vk.delta_g2,
+        })
+    }
+
+    pub fn g1(&self) -> [G1Affine; 4] {
+        [self.neg_a, self.alpha, self.l, self.c]
+    }
+
+    pub fn g2(&self) -> [G2Affine; 4] {
+        [self.b, self.beta, self.gamma, self.delta]
+    }
+
+    /// Whether the product of the pairings is one, that is, the proof verifies
+    pub fn check(&self) -> bool {
+        Bn254::multi_pairing(self.g1(), self.g2()).0.is_one()
+    }
+}
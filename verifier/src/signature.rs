@@ -0,0 +1,224 @@
+use clap::ValueEnum;
+use ed25519_dalek::Signer;
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+
+use crate::bundle::ProofBundle;
+use crate::error::VerifierError;
+use crate::format::read_file_to_string;
+
+const BUNDLE_TAG: &[u8] = b"bitvmx-zk.ProofBundle";
+
+#[derive(Serialize, Deserialize, ValueEnum, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureScheme {
+    /// BIP340 Schnorr over secp256k1
+    Bip340,
+    Ed25519,
+}
+
+impl std::fmt::Display for SignatureScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureScheme::Bip340 => write!(f, "bip340"),
+            SignatureScheme::Ed25519 => write!(f, "ed25519"),
+        }
+    }
+}
+
+/// Operator key file, hex encoded
+#[derive(Serialize, Deserialize)]
+pub struct OperatorKey {
+    pub scheme: SignatureScheme,
+    pub secret_key: String,
+    pub public_key: String,
+}
+
+/// Operator signature embedded in a proof bundle, hex encoded
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BundleSignature {
+    pub scheme: SignatureScheme,
+    pub public_key: String,
+    pub signature: String,
+}
+
+fn invalid_key(e: impl std::fmt::Display) -> VerifierError {
+    VerifierError::MalformedInput(format!("Invalid operator key: {}", e))
+}
+
+fn invalid_signature(e: impl std::fmt::Display) -> VerifierError {
+    VerifierError::InvalidBundle(format!("Invalid operator signature: {}", e))
+}
+
+fn decode(value: &str, what: &str) -> Result<Vec<u8>, VerifierError> {
+    hex::decode(value)
+        .map_err(|e| VerifierError::MalformedInput(format!("Invalid {}: {}", what, e)))
+}
+
+// Every field of the bundle but the signature, each prefixed by its length so
+// that no two bundles hash to the same message
+pub fn bundle_message(bundle: &ProofBundle) -> [u8; 32] {
+    let image_id: Vec<u8> = bundle
+        .image_id
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect();
+
+    let mut hasher = Sha256::new();
+    hasher.update(BUNDLE_TAG);
+    for field in [
+        bundle.risc0_version.as_bytes(),
+        &image_id,
+        bundle.journal.as_bytes(),
+        bundle.seal.as_bytes(),
+        bundle.claim_digest.as_bytes(),
+        bundle.control_root.as_bytes(),
+        bundle.bn254_control_id.as_bytes(),
+    ] {
+        hasher.update((field.len() as u32).to_le_bytes());
+        hasher.update(field);
+    }
+    hasher.finalize().into()
+}
+
+pub fn generate_key(scheme: SignatureScheme) -> OperatorKey {
+    let (secret_key, public_key) = match scheme {
+        SignatureScheme::Bip340 => {
+            let key = k256::schnorr::SigningKey::random(&mut OsRng);
+            (
+                key.to_bytes().to_vec(),
+                key.verifying_key().to_bytes().to_vec(),
+            )
+        }
+        SignatureScheme::Ed25519 => {
+            let key = ed25519_dalek::SigningKey::generate(&mut OsRng);
+            (
+                key.to_bytes().to_vec(),
+                key.verifying_key().to_bytes().to_vec(),
+            )
+        }
+    };
+
+    OperatorKey {
+        scheme,
+        secret_key: hex::encode(secret_key),
+        public_key: hex::encode(public_key),
+    }
+}
+
+// The secret key must stay with the operator, so an existing file is never
+// replaced and the file is only readable by its owner from the start
+pub fn write_key<T: Serialize>(key: &T, name: &str) -> Result<(), VerifierError> {
+    let json = serde_json::to_string_pretty(key)
+        .map_err(|e| VerifierError::Io(format!("Failed to serialize key: {}", e)))?;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(name).map_err(|e| match e.kind() {
+        std::io::ErrorKind::AlreadyExists => {
+            VerifierError::Io(format!("Key file {} already exists", name))
+        }
+        _ => VerifierError::Io(format!("Failed to create key file: {}", name)),
+    })?;
+    file.write_all((json + "\n").as_bytes())
+        .map_err(|_| VerifierError::Io(format!("Failed to write key file: {}", name)))
+}
+
+// Rewrites a key file created by write_key, which keeps its permissions
+pub fn update_key<T: Serialize>(key: &T, name: &str) -> Result<(), VerifierError> {
+    let json = serde_json::to_string_pretty(key)
+        .map_err(|e| VerifierError::Io(format!("Failed to serialize key: {}", e)))?;
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(name)
+        .map_err(|_| VerifierError::Io(format!("Failed to open key file: {}", name)))?;
+    file.write_all((json + "\n").as_bytes())
+        .map_err(|_| VerifierError::Io(format!("Failed to write key file: {}", name)))
+}
+
+pub fn read_key(name: &str) -> Result<OperatorKey, VerifierError> {
+    serde_json::from_str(&read_file_to_string(name)?)
+        .map_err(|e| VerifierError::MalformedInput(format!("Invalid key file {}: {}", name, e)))
+}
+
+pub fn sign_bundle(
+    bundle: &ProofBundle,
+    key: &OperatorKey,
+) -> Result<BundleSignature, VerifierError> {
+    let message = bundle_message(bundle);
+    let secret_key = decode(&key.secret_key, "operator key")?;
+
+    let (public_key, signature) = match key.scheme {
+        SignatureScheme::Bip340 => {
+            let key = k256::schnorr::SigningKey::from_bytes(&secret_key).map_err(invalid_key)?;
+            let mut aux_rand = [0u8; 32];
+            OsRng.fill_bytes(&mut aux_rand);
+            let signature = key
+                .sign_prehash_with_aux_rand(&message, &aux_rand)
+                .map_err(invalid_key)?;
+            (
+                key.verifying_key().to_bytes().to_vec(),
+                signature.to_bytes().to_vec(),
+            )
+        }
+        SignatureScheme::Ed25519 => {
+            let secret_key: [u8; 32] = secret_key
+                .try_into()
+                .map_err(|_| invalid_key("ed25519 keys have 32 bytes"))?;
+            let key = ed25519_dalek::SigningKey::from_bytes(&secret_key);
+            (
+                key.verifying_key().to_bytes().to_vec(),
+                key.sign(&message).to_bytes().to_vec(),
+            )
+        }
+    };
+
+    if hex::encode(&public_key) != key.public_key {
+        return Err(invalid_key("public key does not match the secret key"));
+    }
+
+    Ok(BundleSignature {
+        scheme: key.scheme,
+        public_key: hex::encode(public_key),
+        signature: hex::encode(signature),
+    })
+}
+
+pub fn verify_signature(
+    bundle: &ProofBundle,
+    signature: &BundleSignature,
+) -> Result<(), VerifierError> {
+    let message = bundle_message(bundle);
+    let public_key = decode(&signature.public_key, "operator public key")?;
+    let signature_bytes = decode(&signature.signature, "operator signature")?;
+
+    match signature.scheme {
+        SignatureScheme::Bip340 => {
+            let key =
+                k256::schnorr::VerifyingKey::from_bytes(&public_key).map_err(invalid_signature)?;
+            let signature = k256::schnorr::Signature::try_from(signature_bytes.as_slice())
+                .map_err(invalid_signature)?;
+            key.verify_raw(&message, &signature)
+                .map_err(invalid_signature)
+        }
+        SignatureScheme::Ed25519 => {
+            let public_key: [u8; 32] = public_key
+                .try_into()
+                .map_err(|_| invalid_signature("ed25519 public keys have 32 bytes"))?;
+            let key =
+                ed25519_dalek::VerifyingKey::from_bytes(&public_key).map_err(invalid_signature)?;
+            let signature = ed25519_dalek::Signature::from_slice(&signature_bytes)
+                .map_err(invalid_signature)?;
+            key.verify_strict(&message, &signature)
+                .map_err(invalid_signature)
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G2Affine};
+use ark_ec::pairing::Pairing;
+use ark_ff::{BigInteger, PrimeField};
+use num_bigint::BigUint;
+use risc0_groth16::Seal;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::error::VerifierError;
+use crate::format::{read_file_to_string, write_file};
+
+/// Groth16 proof in the `proof.json` format of snarkjs. Points are affine, with
+/// the projective coordinate fixed to one, and every element is in decimal.
+#[derive(Serialize, Deserialize)]
+pub struct SnarkjsProof {
+    pub pi_a: Vec<String>,
+    pub pi_b: Vec<Vec<String>>,
+    pub pi_c: Vec<String>,
+    pub protocol: Option<String>,
+    pub curve: Option<String>,
+}
+
+/// Groth16 verifying key in the `verification_key.json` format of snarkjs
+#[derive(Serialize, Deserialize)]
+pub struct SnarkjsVerifyingKey {
+    pub protocol: String,
+    pub curve: String,
+    #[serde(rename = "nPublic")]
+    pub n_public: usize,
+    pub vk_alpha_1: Vec<String>,
+    pub vk_beta_2: Vec<Vec<String>>,
+    pub vk_gamma_2: Vec<Vec<String>>,
+    pub vk_delta_2: Vec<Vec<String>>,
+    /// e(alpha, beta), snarkjs skips the pairing with it but older tools require it
+    #[serde(default)]
+    pub vk_alphabeta_12: Vec<Vec<Vec<String>>>,
+    #[serde(rename = "IC")]
+    pub ic: Vec<Vec<String>>,
+}
+
+impl SnarkjsVerifyingKey {
+    pub fn new(vk: &ark_groth16::VerifyingKey<Bn254>) -> Self {
+        let alphabeta = Bn254::pairing(vk.alpha_g1, vk.beta_g2).0;
+        let fq6 =
+            |c: &ark_bn254::Fq6| vec![fq2_decimal(&c.c0), fq2_decimal(&c.c1), fq2_decimal(&c.c2)];
+
+        SnarkjsVerifyingKey {
+            protocol: "groth16".to_string(),
+            curve: "bn128".to_string(),
+            n_public: vk.gamma_abc_g1.len() - 1,
+            vk_alpha_1: g1_decimal(&vk.alpha_g1),
+            vk_beta_2: g2_decimal(&vk.beta_g2),
+            vk_gamma_2: g2_decimal(&vk.gamma_g2),
+            vk_delta_2: g2_decimal(&vk.delta_g2),
+            vk_alphabeta_12: vec![fq6(&alphabeta.c0), fq6(&alphabeta.c1)],
+            ic: vk.gamma_abc_g1.iter().map(g1_decimal).collect(),
+        }
+    }
+
+    // The pairing of alpha and beta is derived from the other elements, so it
+    // is not compared
+    pub fn same_key(&self, other: &SnarkjsVerifyingKey) -> bool {
+        self.n_public == other.n_public
+            && self.vk_alpha_1 == other.vk_alpha_1
+            && self.vk_beta_2 == other.vk_beta_2
+            && self.vk_gamma_2 == other.vk_gamma_2
+            && self.vk_delta_2 == other.vk_delta_2
+            && self.ic == other.ic
+    }
+}
+
+fn malformed(name: &str, e: impl std::fmt::Display) -> VerifierError {
+    VerifierError::MalformedInput(format!("Invalid {}: {}", name, e))
+}
+
+fn decimal(be_bytes: &[u8]) -> String {
+    BigUint::from_bytes_be(be_bytes).to_string()
+}
+
+// 32 big endian bytes of a decimal field element
+fn from_decimal(value: &str, name: &str) -> Result<Vec<u8>, VerifierError> {
+    let bytes = BigUint::from_str(value)
+        .map_err(|e| malformed(name, e))?
+        .to_bytes_be();
+    if bytes.len() > 32 {
+        return Err(malformed(name, "value does not fit in 32 bytes"));
+    }
+    let mut element = vec![0u8; 32 - bytes.len()];
+    element.extend(bytes);
+    Ok(element)
+}
+
+fn fq_decimal(fq: &Fq) -> String {
+    decimal(&fq.into_bigint().to_bytes_be())
+}
+
+fn fq2_decimal(fq2: &Fq2) -> Vec<String> {
+    vec![fq_decimal(&fq2.c0), fq_decimal(&fq2.c1)]
+}
+
+fn g1_decimal(point: &G1Affine) -> Vec<String> {
+    vec![fq_decimal(&point.x), fq_decimal(&point.y), "1".to_string()]
+}
+
+fn g2_decimal(point: &G2Affine) -> Vec<Vec<String>> {
+    vec![
+        fq2_decimal(&point.x),
+        fq2_decimal(&point.y),
+        vec!["1".to_string(), "0".to_string()],
+    ]
+}
+
+// The projective coordinate snarkjs appends to every point must be one
+fn check_affine(z: &[String], one: &[&str], name: &str) -> Result<(), VerifierError> {
+    match z.is_empty() || z.iter().map(String::as_str).eq(one.iter().copied()) {
+        true => Ok(()),
+        false => Err(malformed(name, "the point is not affine")),
+    }
+}
+
+fn g1_from_json(point: &[String], name: &str) -> Result<Vec<Vec<u8>>, VerifierError> {
+    if point.len() < 2 || point.len() > 3 {
+        return Err(malformed(name, "G1 points have two coordinates"));
+    }
+    check_affine(&point[2..], &["1"], name)?;
+    Ok(vec![
+        from_decimal(&point[0], name)?,
+        from_decimal(&point[1], name)?,
+    ])
+}
+
+fn g2_from_json(point: &[Vec<String>], name: &str) -> Result<Vec<Vec<Vec<u8>>>, VerifierError> {
+    if point.len() < 2 || point.len() > 3 || point[0].len() != 2 || point[1].len() != 2 {
+        return Err(malformed(name, "G2 points have two coordinates in Fq2"));
+    }
+    if let Some(z) = point.get(2) {
+        check_affine(z, &["1", "0"], name)?;
+    }
+    // The seal keeps the imaginary part of each coordinate first
+    Ok(vec![
+        vec![
+            from_decimal(&point[0][1], name)?,
+            from_decimal(&point[0][0], name)?,
+        ],
+        vec![
+            from_decimal(&point[1][1], name)?,
+            from_decimal(&point[1][0], name)?,
+        ],
+    ])
+}
+
+pub fn proof_from_seal(seal: &Seal) -> SnarkjsProof {
+    let g1 = |point: &Vec<Vec<u8>>| vec![decimal(&point[0]), decimal(&point[1]), "1".to_string()];
+
+    SnarkjsProof {
+        pi_a: g1(&seal.a),
+        pi_b: vec![
+            vec![decimal(&seal.b[0][1]), decimal(&seal.b[0][0])],
+            vec![decimal(&seal.b[1][1]), decimal(&seal.b[1][0])],
+            vec!["1".to_string(), "0".to_string()],
+        ],
+        pi_c: g1(&seal.c),
+        protocol: Some("groth16".to_string()),
+        curve: Some("bn128".to_string()),
+    }
+}
+
+pub fn seal_from_proof(proof: &SnarkjsProof) -> Result<Seal, VerifierError> {
+    if proof
+        .protocol
+        .as_deref()
+        .is_some_and(|protocol| protocol != "groth16")
+    {
+        return Err(malformed("snarkjs proof", "the protocol is not groth16"));
+    }
+    Ok(Seal {
+        a: g1_from_json(&proof.pi_a, "pi_a")?,
+        b: g2_from_json(&proof.pi_b, "pi_b")?,
+        c: g1_from_json(&proof.pi_c, "pi_c")?,
+    })
+}
+
+pub fn read_json<T: for<'de> Deserialize<'de>>(name: &str) -> Result<T, VerifierError> {
+    serde_json::from_str(&read_file_to_string(name)?)
+        .map_err(|e| VerifierError::MalformedInput(format!("Invalid snarkjs file {}: {}", name, e)))
+}
+
+pub fn write_json<T: Serialize>(value: &T, name: &str) -> Result<(), VerifierError> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| VerifierError::Io(format!("Failed to serialize {}: {}", name, e)))?;
+    write_file(name, json + "\n")
+}
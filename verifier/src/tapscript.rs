@@ -0,0 +1,183 @@
+use bitcoin::opcodes::all::*;
+use bitcoin::opcodes::Opcode;
+use bitcoin::script::{Builder, PushBytes, ScriptBuf};
+
+use crate::error::VerifierError;
+use crate::winternitz::{WinternitzHash, WinternitzParams, WinternitzSignature};
+use crate::witness::MAX_SCRIPT_ELEMENT_SIZE;
+
+/// Consensus limit of the elements in the stack and the altstack together
+pub const MAX_STACK_SIZE: usize = 1000;
+/// Policy limit of every witness element of a P2WSH spend. Tapscript spends
+/// have no such limit, only the consensus one of `MAX_SCRIPT_ELEMENT_SIZE`.
+pub const MAX_STANDARD_P2WSH_STACK_ITEM_SIZE: usize = 80;
+
+// Elements the fragment needs on top of the witness while it runs
+const WORKING_STACK: usize = 4;
+
+fn hash_opcode(hash: WinternitzHash) -> Opcode {
+    match hash {
+        WinternitzHash::Sha256 => OP_SHA256,
+        WinternitzHash::Hash160 => OP_HASH160,
+    }
+}
+
+fn check_stack_size(params: &WinternitzParams) -> Result<(), VerifierError> {
+    let size = 2 * params.total_digits() + WORKING_STACK;
+    match size <= MAX_STACK_SIZE {
+        true => Ok(()),
+        false => Err(VerifierError::MalformedInput(format!(
+            "The script needs {} stack elements, more than {}, use wider digits",
+            size, MAX_STACK_SIZE
+        ))),
+    }
+}
+
+// Takes `signature digit`, with the digit on top, and moves the digit to the
+// altstack once the signature hashed `max_digit - digit` times is the public key
+fn verify_digit(builder: Builder, params: &WinternitzParams, public_key: &[u8]) -> Builder {
+    let max = params.max_digit() as i64;
+    let mut builder = builder
+        .push_opcode(OP_DUP)
+        .push_int(0)
+        .push_int(max + 1)
+        .push_opcode(OP_WITHIN)
+        .push_opcode(OP_VERIFY)
+        .push_opcode(OP_DUP)
+        .push_opcode(OP_TOALTSTACK)
+        .push_int(max)
+        .push_opcode(OP_SWAP)
+        .push_opcode(OP_SUB);
+
+    for _ in 0..max {
+        builder = builder
+            .push_opcode(OP_DUP)
+            .push_opcode(OP_0NOTEQUAL)
+            .push_opcode(OP_IF)
+            .push_opcode(OP_SWAP)
+            .push_opcode(hash_opcode(params.hash))
+            .push_opcode(OP_SWAP)
+            .push_opcode(OP_1SUB)
+            .push_opcode(OP_ENDIF);
+    }
+
+    let public_key: &PushBytes = public_key
+        .try_into()
+        .expect("public keys are at most 32 bytes");
+    builder
+        .push_opcode(OP_DROP)
+        .push_slice(public_key)
+        .push_opcode(OP_EQUALVERIFY)
+}
+
+fn fragment_builder(
+    params: &WinternitzParams,
+    public_key: &[String],
+) -> Result<Builder, VerifierError> {
+    check_stack_size(params)?;
+    if public_key.len() != params.total_digits() {
+        return Err(VerifierError::MalformedInput(format!(
+            "The public key has {} digits instead of {}",
+            public_key.len(),
+            params.total_digits()
+        )));
+    }
+
+    let mut builder = Builder::new();
+    for public in public_key {
+        let public = hex::decode(public)
+            .ok()
+            .filter(|public| public.len() == params.hash.size())
+            .ok_or_else(|| {
+                VerifierError::MalformedInput(format!("Invalid Winternitz public key {}", public))
+            })?;
+        builder = verify_digit(builder, params, &public);
+    }
+
+    // The checksum digits come out of the altstack least significant first
+    builder = builder.push_opcode(OP_FROMALTSTACK);
+    for digit in 1..params.checksum_digits() {
+        builder = builder.push_opcode(OP_FROMALTSTACK);
+        for _ in 0..digit * params.digit_bits as usize {
+            builder = builder.push_opcode(OP_DUP).push_opcode(OP_ADD);
+        }
+        builder = builder.push_opcode(OP_ADD);
+    }
+
+    // Subtracts `max_digit - digit` of every message digit from the checksum,
+    // leaving the digit under it
+    let max = params.max_digit() as i64;
+    for _ in 0..params.message_digits() {
+        builder = builder
+            .push_opcode(OP_FROMALTSTACK)
+            .push_opcode(OP_DUP)
+            .push_int(max)
+            .push_opcode(OP_SWAP)
+            .push_opcode(OP_SUB)
+            .push_opcode(OP_ROT)
+            .push_opcode(OP_SWAP)
+            .push_opcode(OP_SUB);
+    }
+    Ok(builder.push_int(0).push_opcode(OP_NUMEQUALVERIFY))
+}
+
+/// Script that checks the Winternitz signature of a message and leaves its
+/// digits on the stack, the first one on top, for the scripts that follow
+pub fn fragment(
+    params: &WinternitzParams,
+    public_key: &[String],
+) -> Result<ScriptBuf, VerifierError> {
+    Ok(fragment_builder(params, public_key)?.into_script())
+}
+
+/// Tapleaf with the fragment alone: it drops the digits and succeeds
+pub fn leaf_script(
+    params: &WinternitzParams,
+    public_key: &[String],
+) -> Result<ScriptBuf, VerifierError> {
+    let mut builder = fragment_builder(params, public_key)?;
+    for _ in 0..params.message_digits() / 2 {
+        builder = builder.push_opcode(OP_2DROP);
+    }
+    if params.message_digits() % 2 == 1 {
+        builder = builder.push_opcode(OP_DROP);
+    }
+    Ok(builder.push_opcode(OP_PUSHNUM_1).into_script())
+}
+
+// Minimally encoded script number, as MINIMALDATA requires
+fn script_num(value: u32) -> Vec<u8> {
+    let mut bytes: Vec<u8> = value.to_le_bytes().to_vec();
+    while bytes.last() == Some(&0) {
+        bytes.pop();
+    }
+    if bytes.last().is_some_and(|byte| byte & 0x80 != 0) {
+        bytes.push(0);
+    }
+    bytes
+}
+
+/// Witness elements the fragment takes, bottom first: the signature and the
+/// digit of every digit, the first digit on top
+pub fn witness(
+    params: &WinternitzParams,
+    signature: &WinternitzSignature,
+) -> Result<Vec<Vec<u8>>, VerifierError> {
+    check_stack_size(params)?;
+
+    let mut witness = Vec::new();
+    for (hash, digit) in signature.signature.iter().zip(&signature.digits).rev() {
+        let hash = hex::decode(hash).map_err(|e| {
+            VerifierError::MalformedInput(format!("Invalid Winternitz signature: {}", e))
+        })?;
+        if hash.len() > MAX_SCRIPT_ELEMENT_SIZE {
+            return Err(VerifierError::MalformedInput(format!(
+                "Witness elements are at most {} bytes",
+                MAX_SCRIPT_ELEMENT_SIZE
+            )));
+        }
+        witness.push(hash);
+        witness.push(script_num(*digit));
+    }
+    Ok(witness)
+}
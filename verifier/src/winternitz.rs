@@ -0,0 +1,364 @@
+use clap::ValueEnum;
+use rand_core::{OsRng, RngCore};
+use ripemd::Ripemd160;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::VerifierError;
+use crate::format::read_file_to_string;
+
+const SECRET_TAG: &[u8] = b"bitvmx-zk.Winternitz";
+
+/// Size of the compressed proof, A and C of 32 bytes and B of 64 bytes
+pub const PROOF_SIZE: usize = 128;
+
+#[derive(Serialize, Deserialize, ValueEnum, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum WinternitzHash {
+    Sha256,
+    /// RIPEMD-160 of SHA-256, as OP_HASH160
+    Hash160,
+}
+
+impl WinternitzHash {
+    pub fn hash(&self, data: &[u8]) -> Vec<u8> {
+        let sha256 = Sha256::digest(data);
+        match self {
+            WinternitzHash::Sha256 => sha256.to_vec(),
+            WinternitzHash::Hash160 => Ripemd160::digest(sha256).to_vec(),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match self {
+            WinternitzHash::Sha256 => 32,
+            WinternitzHash::Hash160 => 20,
+        }
+    }
+
+    // The value hashed the given number of times
+    pub fn chain(&self, value: &[u8], times: u32) -> Vec<u8> {
+        (0..times).fold(value.to_vec(), |value, _| self.hash(&value))
+    }
+}
+
+impl std::fmt::Display for WinternitzHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WinternitzHash::Sha256 => write!(f, "sha256"),
+            WinternitzHash::Hash160 => write!(f, "hash160"),
+        }
+    }
+}
+
+/// Layout of the digits that sign a message of a fixed size: the message split
+/// in digits of `digit_bits` bits, most significant first, followed by the
+/// digits of the checksum, the sum of `max_digit - digit` over the message
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct WinternitzParams {
+    pub hash: WinternitzHash,
+    pub digit_bits: u8,
+    pub message_size: usize,
+}
+
+impl WinternitzParams {
+    pub fn new(
+        hash: WinternitzHash,
+        digit_bits: u8,
+        message_size: usize,
+    ) -> Result<Self, VerifierError> {
+        if !(1..=8).contains(&digit_bits) {
+            return Err(VerifierError::MalformedInput(format!(
+                "Winternitz digits have from 1 to 8 bits, got {}",
+                digit_bits
+            )));
+        }
+        Ok(WinternitzParams {
+            hash,
+            digit_bits,
+            message_size,
+        })
+    }
+
+    pub fn max_digit(&self) -> u32 {
+        (1 << self.digit_bits) - 1
+    }
+
+    pub fn message_digits(&self) -> usize {
+        (self.message_size * 8).div_ceil(self.digit_bits as usize)
+    }
+
+    pub fn checksum_digits(&self) -> usize {
+        let mut max_checksum = self.message_digits() as u64 * self.max_digit() as u64;
+        let mut digits = 1;
+        while max_checksum > self.max_digit() as u64 {
+            max_checksum >>= self.digit_bits;
+            digits += 1;
+        }
+        digits
+    }
+
+    pub fn total_digits(&self) -> usize {
+        self.message_digits() + self.checksum_digits()
+    }
+
+    /// Digits of the message and of its checksum, the last message digit padded
+    /// with zero bits
+    pub fn digits(&self, message: &[u8]) -> Result<Vec<u32>, VerifierError> {
+        if message.len() != self.message_size {
+            return Err(VerifierError::MalformedInput(format!(
+                "The Winternitz key signs {} bytes, got {}",
+                self.message_size,
+                message.len()
+            )));
+        }
+
+        let bit = |i: usize| match message.get(i / 8) {
+            Some(byte) => (byte >> (7 - i % 8)) as u32 & 1,
+            None => 0,
+        };
+        let bits = self.digit_bits as usize;
+        let mut digits: Vec<u32> = (0..self.message_digits())
+            .map(|digit| (0..bits).fold(0, |value, i| value << 1 | bit(digit * bits + i)))
+            .collect();
+
+        let checksum: u64 = digits.iter().map(|d| (self.max_digit() - d) as u64).sum();
+        digits.extend(
+            (0..self.checksum_digits())
+                .rev()
+                .map(|i| (checksum >> (i * bits)) as u32 & self.max_digit()),
+        );
+        Ok(digits)
+    }
+
+    /// Message bytes back from its digits, without the checksum
+    pub fn message(&self, digits: &[u32]) -> Vec<u8> {
+        let bits = self.digit_bits as usize;
+        let mut message = vec![0u8; self.message_size];
+        for (digit, value) in digits[..self.message_digits()].iter().enumerate() {
+            for i in 0..bits {
+                let position = digit * bits + i;
+                if position / 8 < self.message_size && value >> (bits - 1 - i) & 1 == 1 {
+                    message[position / 8] |= 1 << (7 - position % 8);
+                }
+            }
+        }
+        message
+    }
+}
+
+/// Winternitz key file. The secret of every digit is derived from the seed,
+/// which is hex encoded. Each key signs only one message: the signatures of two
+/// messages reveal enough of the chains to forge others. `signed` is the hex
+/// encoded digest of the journal and proof once they are signed.
+#[derive(Serialize, Deserialize)]
+pub struct WinternitzKey {
+    pub hash: WinternitzHash,
+    pub digit_bits: u8,
+    pub journal_size: usize,
+    pub seed: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed: Option<String>,
+}
+
+/// Public keys of the journal and of the proof, one hex encoded hash per digit
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct WinternitzPublicKeys {
+    pub hash: WinternitzHash,
+    pub digit_bits: u8,
+    pub journal_size: usize,
+    pub journal: Vec<String>,
+    pub proof: Vec<String>,
+}
+
+/// Signed message with one hex encoded hash per digit
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WinternitzSignature {
+    pub message: String,
+    pub digits: Vec<u32>,
+    pub signature: Vec<String>,
+}
+
+/// Winternitz signatures of the journal and of the compressed proof
+#[derive(Serialize, Deserialize)]
+pub struct WinternitzSignatures {
+    pub hash: WinternitzHash,
+    pub digit_bits: u8,
+    pub journal: WinternitzSignature,
+    pub proof: WinternitzSignature,
+}
+
+fn invalid_key(e: impl std::fmt::Display) -> VerifierError {
+    VerifierError::MalformedInput(format!("Invalid Winternitz key: {}", e))
+}
+
+fn invalid_signature(name: &str, e: impl std::fmt::Display) -> VerifierError {
+    VerifierError::InvalidSignature(format!("Winternitz signature of the {}: {}", name, e))
+}
+
+impl WinternitzKey {
+    pub fn generate(hash: WinternitzHash, digit_bits: u8, journal_size: usize) -> Self {
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        WinternitzKey {
+            hash,
+            digit_bits,
+            journal_size,
+            seed: hex::encode(seed),
+            signed: None,
+        }
+    }
+
+    pub fn journal_params(&self) -> Result<WinternitzParams, VerifierError> {
+        WinternitzParams::new(self.hash, self.digit_bits, self.journal_size)
+    }
+
+    pub fn proof_params(&self) -> Result<WinternitzParams, VerifierError> {
+        WinternitzParams::new(self.hash, self.digit_bits, PROOF_SIZE)
+    }
+
+    // The journal and the proof are signed with secrets of different labels
+    fn secrets(&self, label: &str, count: usize) -> Result<Vec<Vec<u8>>, VerifierError> {
+        let seed = hex::decode(&self.seed).map_err(invalid_key)?;
+        Ok((0..count as u32)
+            .map(|digit| {
+                let mut hasher = Sha256::new();
+                hasher.update(SECRET_TAG);
+                hasher.update(&seed);
+                hasher.update(label.as_bytes());
+                hasher.update(digit.to_le_bytes());
+                hasher.finalize()[..self.hash.size()].to_vec()
+            })
+            .collect())
+    }
+
+    fn public_key(
+        &self,
+        label: &str,
+        params: &WinternitzParams,
+    ) -> Result<Vec<String>, VerifierError> {
+        Ok(self
+            .secrets(label, params.total_digits())?
+            .iter()
+            .map(|secret| hex::encode(self.hash.chain(secret, params.max_digit())))
+            .collect())
+    }
+
+    pub fn public_keys(&self) -> Result<WinternitzPublicKeys, VerifierError> {
+        Ok(WinternitzPublicKeys {
+            hash: self.hash,
+            digit_bits: self.digit_bits,
+            journal_size: self.journal_size,
+            journal: self.public_key("journal", &self.journal_params()?)?,
+            proof: self.public_key("proof", &self.proof_params()?)?,
+        })
+    }
+
+    fn sign(
+        &self,
+        label: &str,
+        params: &WinternitzParams,
+        message: &[u8],
+    ) -> Result<WinternitzSignature, VerifierError> {
+        let digits = params.digits(message)?;
+        let signature = self
+            .secrets(label, params.total_digits())?
+            .iter()
+            .zip(&digits)
+            .map(|(secret, digit)| hex::encode(self.hash.chain(secret, *digit)))
+            .collect();
+
+        Ok(WinternitzSignature {
+            message: hex::encode(message),
+            digits,
+            signature,
+        })
+    }
+
+    /// Signs the journal and the proof, and marks the key as used. Signing the
+    /// same message again is allowed, as it reveals no other secrets.
+    pub fn sign_proof(
+        &mut self,
+        journal: &[u8],
+        proof: &[u8],
+    ) -> Result<WinternitzSignatures, VerifierError> {
+        // The journal size is fixed by the key and the proof size is constant
+        let digest = hex::encode(Sha256::digest([journal, proof].concat()));
+        match &self.signed {
+            Some(signed) if *signed != digest => {
+                return Err(VerifierError::MalformedInput(
+                    "The Winternitz key has already signed another message".to_string(),
+                ))
+            }
+            _ => self.signed = Some(digest),
+        }
+
+        Ok(WinternitzSignatures {
+            hash: self.hash,
+            digit_bits: self.digit_bits,
+            journal: self.sign("journal", &self.journal_params()?, journal)?,
+            proof: self.sign("proof", &self.proof_params()?, proof)?,
+        })
+    }
+}
+
+impl WinternitzPublicKeys {
+    pub fn journal_params(&self) -> Result<WinternitzParams, VerifierError> {
+        WinternitzParams::new(self.hash, self.digit_bits, self.journal_size)
+    }
+
+    pub fn proof_params(&self) -> Result<WinternitzParams, VerifierError> {
+        WinternitzParams::new(self.hash, self.digit_bits, PROOF_SIZE)
+    }
+}
+
+/// Checks the signature against the public key and returns the signed message
+pub fn verify_signature(
+    name: &str,
+    params: &WinternitzParams,
+    public_key: &[String],
+    signature: &WinternitzSignature,
+) -> Result<Vec<u8>, VerifierError> {
+    if signature.signature.len() != public_key.len() || signature.digits.len() != public_key.len() {
+        return Err(invalid_signature(name, "wrong number of digits"));
+    }
+    let message = hex::decode(&signature.message).map_err(|e| invalid_signature(name, e))?;
+    if message.len() != params.message_size {
+        return Err(invalid_signature(name, "wrong message size"));
+    }
+    if params.digits(&message)? != signature.digits {
+        return Err(invalid_signature(
+            name,
+            "the digits are not the ones of the message",
+        ));
+    }
+
+    for (i, ((digit, hash), public)) in signature
+        .digits
+        .iter()
+        .zip(&signature.signature)
+        .zip(public_key)
+        .enumerate()
+    {
+        let hash = hex::decode(hash).map_err(|e| invalid_signature(name, e))?;
+        if hash.len() != params.hash.size() {
+            return Err(invalid_signature(
+                name,
+                format!("digit {} has the wrong size", i),
+            ));
+        }
+        if hex::encode(params.hash.chain(&hash, params.max_digit() - digit)) != *public {
+            return Err(invalid_signature(
+                name,
+                format!("digit {} does not match", i),
+            ));
+        }
+    }
+    Ok(message)
+}
+
+pub fn read_json<T: for<'de> Deserialize<'de>>(name: &str) -> Result<T, VerifierError> {
+    serde_json::from_str(&read_file_to_string(name)?).map_err(|e| {
+        VerifierError::MalformedInput(format!("Invalid Winternitz file {}: {}", name, e))
+    })
+}
@@ -0,0 +1,163 @@
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::error::VerifierError;
+use crate::tapscript::MAX_STACK_SIZE;
+
+/// Consensus limit of every element pushed to the stack
+pub const MAX_SCRIPT_ELEMENT_SIZE: usize = 520;
+
+#[derive(Serialize, ValueEnum, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum WitnessPacking {
+    /// The bytes as they are
+    Byte,
+    /// One element per nibble, the high nibble first, minimally encoded as a
+    /// script number (0 is the empty element) for scripts that work on 4-bit
+    /// numbers
+    Nibble,
+}
+
+impl std::fmt::Display for WitnessPacking {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WitnessPacking::Byte => write!(f, "byte"),
+            WitnessPacking::Nibble => write!(f, "nibble"),
+        }
+    }
+}
+
+fn malformed(e: impl std::fmt::Display) -> VerifierError {
+    VerifierError::MalformedInput(format!("Invalid witness: {}", e))
+}
+
+fn check_chunk_size(chunk_size: usize) -> Result<(), VerifierError> {
+    match (1..=MAX_SCRIPT_ELEMENT_SIZE).contains(&chunk_size) {
+        true => Ok(()),
+        false => Err(VerifierError::MalformedInput(format!(
+            "Witness elements have from 1 to {} bytes, got {}",
+            MAX_SCRIPT_ELEMENT_SIZE, chunk_size
+        ))),
+    }
+}
+
+// Minimal script number encoding of a nibble
+fn nibble_element(nibble: u8) -> Vec<u8> {
+    match nibble {
+        0 => vec![],
+        _ => vec![nibble],
+    }
+}
+
+/// Splits the data in elements, in the order of the data: bytes in elements
+/// of at most `chunk_size` bytes, nibbles in an element each
+pub fn pack(
+    data: &[u8],
+    packing: WitnessPacking,
+    chunk_size: usize,
+) -> Result<Vec<Vec<u8>>, VerifierError> {
+    check_chunk_size(chunk_size)?;
+    Ok(match packing {
+        WitnessPacking::Byte => data.chunks(chunk_size).map(<[u8]>::to_vec).collect(),
+        WitnessPacking::Nibble => data
+            .iter()
+            .flat_map(|byte| [byte >> 4, byte & 15])
+            .map(nibble_element)
+            .collect(),
+    })
+}
+
+/// Data back from its elements, in the order of the data
+pub fn unpack(elements: &[Vec<u8>], packing: WitnessPacking) -> Result<Vec<u8>, VerifierError> {
+    if let Some(element) = elements
+        .iter()
+        .find(|element| element.len() > MAX_SCRIPT_ELEMENT_SIZE)
+    {
+        return Err(malformed(format!(
+            "an element has {} bytes, more than {}",
+            element.len(),
+            MAX_SCRIPT_ELEMENT_SIZE
+        )));
+    }
+
+    match packing {
+        WitnessPacking::Byte => Ok(elements.concat()),
+        WitnessPacking::Nibble => {
+            if elements.len() % 2 == 1 {
+                return Err(malformed("odd number of nibbles"));
+            }
+            let nibbles = elements
+                .iter()
+                .map(|element| match element.as_slice() {
+                    [] => Ok(0),
+                    [nibble @ 1..=15] => Ok(*nibble),
+                    _ => Err(malformed(format!(
+                        "{} is not a minimally encoded nibble",
+                        hex::encode(element)
+                    ))),
+                })
+                .collect::<Result<Vec<u8>, VerifierError>>()?;
+            Ok(nibbles
+                .chunks(2)
+                .map(|nibbles| nibbles[0] << 4 | nibbles[1])
+                .collect())
+        }
+    }
+}
+
+/// Witness stack of the journal followed by the compressed proof, bottom
+/// first, so that the first element of the journal ends on top of the stack
+pub fn witness(
+    journal: &[u8],
+    proof: &[u8],
+    packing: WitnessPacking,
+    chunk_size: usize,
+) -> Result<Vec<Vec<u8>>, VerifierError> {
+    let mut elements = pack(journal, packing, chunk_size)?;
+    elements.extend(pack(proof, packing, chunk_size)?);
+    elements.reverse();
+    Ok(elements)
+}
+
+/// Journal and compressed proof back from a witness stack, bottom first. The
+/// proof is the last `proof_size` bytes, the journal whatever comes before.
+pub fn parse_witness(
+    witness: &[Vec<u8>],
+    packing: WitnessPacking,
+    proof_size: usize,
+) -> Result<(Vec<u8>, Vec<u8>), VerifierError> {
+    let elements: Vec<Vec<u8>> = witness.iter().rev().cloned().collect();
+    let mut journal = unpack(&elements, packing)?;
+    if journal.len() < proof_size {
+        return Err(malformed(format!(
+            "{} bytes, fewer than the {} of the proof",
+            journal.len(),
+            proof_size
+        )));
+    }
+    let proof = journal.split_off(journal.len() - proof_size);
+    Ok((journal, proof))
+}
+
+/// Whether a tapscript spend with the witness is relayed. Tapscript has no
+/// policy limit on the elements, so the stack and every element only have to
+/// be within the consensus limits.
+pub fn is_standard(witness: &[Vec<u8>]) -> bool {
+    witness.len() <= MAX_STACK_SIZE
+        && witness
+            .iter()
+            .all(|element| element.len() <= MAX_SCRIPT_ELEMENT_SIZE)
+}
+
+/// Witness elements from a file, either a JSON array of hex strings or hex
+/// strings separated by whitespace, bottom first
+pub fn read_witness(contents: &str) -> Result<Vec<Vec<u8>>, VerifierError> {
+    let elements: Vec<String> = match contents.trim_start().starts_with('[') {
+        true => serde_json::from_str(contents).map_err(malformed)?,
+        false => contents.split_whitespace().map(str::to_string).collect(),
+    };
+    elements
+        .iter()
+        .map(|element| hex::decode(element).map_err(malformed))
+        .collect()
+}
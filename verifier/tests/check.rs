@@ -0,0 +1,112 @@
+use ark_bn254::{Fq, Fq2, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+use risc0_groth16::Seal;
+mod common;
+
+use common::{be, g1, g2, generator_seal};
+use verifier::check::{check_seal, SealCheckError};
+use verifier::format::get_image_id;
+use verifier::{proof_as_input, ProofCheck};
+
+// Checks the error and that its message names the failing element
+fn assert_rejected(seal: &Seal, expected: SealCheckError, element: &str) {
+    let error = check_seal(seal).unwrap_err();
+    assert_eq!(error, expected);
+    assert!(error.to_string().starts_with(element), "{}", error);
+}
+
+#[test]
+fn valid_points_pass() {
+    assert_eq!(check_seal(&generator_seal()), Ok(()));
+}
+
+#[test]
+fn coordinates_must_be_below_the_modulus() {
+    let mut bad = generator_seal();
+    bad.a[0] = Fq::MODULUS.to_bytes_be();
+    assert_rejected(&bad, SealCheckError::NotInField("A.x".into()), "A.x");
+
+    let mut bad = generator_seal();
+    bad.b[1][0] = vec![0xff; 32];
+    assert_rejected(&bad, SealCheckError::NotInField("B.y.c1".into()), "B.y.c1");
+}
+
+#[test]
+fn coordinates_must_have_32_bytes() {
+    let mut bad = generator_seal();
+    bad.c[1] = bad.c[1][1..].to_vec();
+    assert_rejected(&bad, SealCheckError::NotCanonical("C.y".into()), "C.y");
+
+    let mut bad = generator_seal();
+    bad.b[0][1].insert(0, 0);
+    assert_rejected(
+        &bad,
+        SealCheckError::NotCanonical("B.x.c0".into()),
+        "B.x.c0",
+    );
+
+    let mut bad = generator_seal();
+    bad.b.pop();
+    assert_rejected(&bad, SealCheckError::Malformed("B".into()), "B");
+}
+
+#[test]
+fn points_at_infinity_are_rejected() {
+    let mut bad = generator_seal();
+    bad.a = vec![vec![0; 32], vec![0; 32]];
+    assert_rejected(&bad, SealCheckError::PointAtInfinity("A".into()), "A");
+
+    let mut bad = generator_seal();
+    bad.b = vec![vec![vec![0; 32]; 2]; 2];
+    assert_rejected(&bad, SealCheckError::PointAtInfinity("B".into()), "B");
+}
+
+#[test]
+fn points_off_the_curve_are_rejected() {
+    let mut bad = generator_seal();
+    bad.c = g1(&G1Affine::new_unchecked(Fq::from(1u8), Fq::from(3u8)));
+    assert_rejected(&bad, SealCheckError::NotOnCurve("C".into()), "C");
+
+    let mut bad = generator_seal();
+    bad.b[1][1] = be(&Fq::from(5u8));
+    assert_rejected(&bad, SealCheckError::NotOnCurve("B".into()), "B");
+}
+
+// The twist has a large cofactor, so the first point found on it is almost
+// never in the prime order subgroup
+#[test]
+fn g2_points_outside_the_subgroup_are_rejected() {
+    let point = (1u64..)
+        .filter_map(|x| {
+            G2Affine::get_point_from_x_unchecked(Fq2::new(x.into(), Fq::from(0u8)), true)
+        })
+        .find(|point| !point.is_in_correct_subgroup_assuming_on_curve())
+        .unwrap();
+    assert!(point.is_on_curve());
+
+    let mut bad = generator_seal();
+    bad.b = g2(&point);
+    assert_rejected(&bad, SealCheckError::NotInSubgroup("B".into()), "B");
+}
+
+// Library callers name the check, no image id means nothing here
+#[test]
+fn proofs_are_verified_unless_skipped_explicitly() {
+    let image_id =
+        get_image_id(&format!("{}/../image_id.json", env!("CARGO_MANIFEST_DIR"))).unwrap();
+    let journal = [1, 0, 0, 0];
+
+    let verified = proof_as_input(ProofCheck::Verify(&image_id), &journal, &generator_seal());
+    assert_eq!(verified.err().map(|e| e.exit_code()), Some(1));
+    assert!(proof_as_input(ProofCheck::SkipVerify, &journal, &generator_seal()).is_ok());
+
+    // The seal points are checked either way
+    let mut bad = generator_seal();
+    bad.a = vec![vec![0; 32], vec![0; 32]];
+    assert!(proof_as_input(ProofCheck::SkipVerify, &journal, &bad).is_err());
+    assert_eq!(ProofCheck::new(&image_id, true), ProofCheck::SkipVerify);
+    assert_eq!(
+        ProofCheck::new(&image_id, false),
+        ProofCheck::Verify(&image_id)
+    );
+}
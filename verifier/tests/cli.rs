@@ -0,0 +1,951 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::bn::G2Prepared;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField};
+use common::generator_seal;
+use risc0_zkvm::Groth16ReceiptVerifierParameters;
+use verifier::bundle::{write_bundle, ProofBundle};
+use verifier::format::{get_image_id, get_journal_from_result};
+
+// Claim digest of the committed image id with the journal 01000000
+const CLAIM_DIGEST: &str = "d6c72b5f530a1ed33eb9d657c2c6eda891b47965d0472aa46a3465aa701160ca";
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("verifier-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn image_id() -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../image_id.json")
+        .display()
+        .to_string()
+}
+
+fn write_seal(dir: &Path, seal: &[u8]) -> String {
+    let path = dir.join("seal.json");
+    let result = serde_json::json!({
+        "type": "ProveResult",
+        "data": { "seal": seal, "status": "OK", "journal": [1, 0, 0, 0] },
+    });
+    std::fs::write(&path, result.to_string()).unwrap();
+    path.display().to_string()
+}
+
+// Template with the given placeholders, one per line, and the file it is set up into
+fn template_setup(dir: &Path, placeholders: &[&str], args: &[&str]) -> Vec<String> {
+    let template = dir.join("template.h");
+    let output = dir.join("constants.h");
+    std::fs::write(&template, placeholders.join("\n")).unwrap();
+
+    let image_id = image_id();
+    let template = template.display().to_string();
+    let output_file = output.display().to_string();
+    let mut setup = vec![
+        "template-setup",
+        "-i",
+        &image_id,
+        "-t",
+        &template,
+        "--header",
+        &output_file,
+    ];
+    setup.extend(args);
+    assert!(verifier(&setup).status.success());
+
+    std::fs::read_to_string(output)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+// Points of the verifying key, from the snarkjs export
+fn snarkjs_verifying_key(dir: &Path) -> serde_json::Value {
+    let seal = write_seal(dir, &generator_seal().to_vec());
+    let file = |name: &str| dir.join(name).display().to_string();
+    json_output(&verifier(&[
+        "export-snarkjs",
+        "--journal",
+        "1,0,0,0",
+        "-s",
+        &seal,
+        "-i",
+        &image_id(),
+        "--skip-verify",
+        "--proof",
+        &file("proof.json"),
+        "--public",
+        &file("public.json"),
+        "--verification-key",
+        &file("vk.json"),
+        "--json",
+    ]));
+    serde_json::from_str(&std::fs::read_to_string(dir.join("vk.json")).unwrap()).unwrap()
+}
+
+fn decimal<F: PrimeField>(value: &serde_json::Value) -> F {
+    F::from_str(value.as_str().unwrap()).unwrap_or_else(|_| panic!("{}", value))
+}
+
+fn g1_from_json(point: &serde_json::Value) -> G1Affine {
+    G1Affine::new(decimal(&point[0]), decimal(&point[1]))
+}
+
+fn g2_from_json(point: &serde_json::Value) -> G2Affine {
+    let fq2 = |c: &serde_json::Value| Fq2::new(decimal(&c[0]), decimal(&c[1]));
+    G2Affine::new(fq2(&point[0]), fq2(&point[1]))
+}
+
+fn c_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(u8::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// x in little endian, with the parity of y in the top bit
+fn g1_c_bytes(point: &G1Affine) -> String {
+    let mut x = point.x.into_bigint().to_bytes_le();
+    if point.y.into_bigint().is_odd() {
+        x[31] |= 0x80;
+    }
+    c_bytes(&x)
+}
+
+fn verifier(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_verifier"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn json_output(output: &Output) -> serde_json::Value {
+    assert!(output.status.success(), "{:?}", output);
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn generate_claim_json() {
+    let output = verifier(&[
+        "generate-claim",
+        "-i",
+        &image_id(),
+        "--journal-u32",
+        "1",
+        "--json",
+    ]);
+    let claim = json_output(&output);
+
+    assert_eq!(claim["journal"], "01000000");
+    assert_eq!(
+        claim["image_id"],
+        "6362fbd71058c3a524356bceda7543ed7bd2fad51f3a943403d5434a31606b54"
+    );
+    assert_eq!(claim["claim_digest"], CLAIM_DIGEST);
+}
+
+#[test]
+fn proof_as_input_json() {
+    let dir = temp_dir("proof-as-input");
+    let seal = write_seal(&dir, &generator_seal().to_vec());
+
+    let output = verifier(&[
+        "proof-as-input",
+        "--journal-result",
+        &seal,
+        "-s",
+        &seal,
+        "--skip-verify",
+        "--json",
+    ]);
+    let input = json_output(&output);
+
+    let journal = input["journal"].as_str().unwrap();
+    let proof = input["proof"].as_str().unwrap();
+    assert_eq!(journal, "01000000");
+    // A and C are 32 bytes and B 64 bytes in the C layout
+    assert_eq!(proof.len(), 2 * 128);
+    assert_eq!(input["input"], format!("{}{}", journal, proof));
+}
+
+// Every subcommand, as listed by the help, has a JSON mode and an output file
+#[test]
+fn every_command_has_json_and_output() {
+    let help = verifier(&["--help"]);
+    let help = String::from_utf8(help.stdout).unwrap();
+    let commands: Vec<&str> = help
+        .lines()
+        .skip_while(|line| *line != "Commands:")
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|command| *command != "help")
+        .collect();
+    assert!(commands.contains(&"template-proof"), "{}", help);
+
+    for command in commands {
+        let output = verifier(&[command, "--help"]);
+        let help = String::from_utf8(output.stdout).unwrap();
+        assert!(help.contains("--json"), "{} has no --json", command);
+        assert!(
+            help.contains("Write the result to this file instead of stdout"),
+            "{} has no result file",
+            command
+        );
+    }
+}
+
+#[test]
+fn exit_codes() {
+    let dir = temp_dir("exit-codes");
+
+    let seal = write_seal(&dir, &generator_seal().to_vec());
+    let output = verifier(&[
+        "verify",
+        "-i",
+        &image_id(),
+        "--journal-u32",
+        "1",
+        "-s",
+        &seal,
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let seal = write_seal(&dir, &[0u8; 100]);
+    let output = verifier(&[
+        "verify",
+        "-i",
+        &image_id(),
+        "--journal-u32",
+        "1",
+        "-s",
+        &seal,
+    ]);
+    assert_eq!(output.status.code(), Some(65));
+
+    let missing = dir.join("missing.json").display().to_string();
+    let output = verifier(&[
+        "verify",
+        "-i",
+        &image_id(),
+        "--journal-u32",
+        "1",
+        "-s",
+        &missing,
+    ]);
+    assert_eq!(output.status.code(), Some(74));
+
+    let output = verifier(&["verify", "-i", &image_id()]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn journals_and_image_ids_are_told_apart() {
+    let dir = temp_dir("formats");
+    let file = |name: &str| dir.join(name).display().to_string();
+
+    // An empty journal is a journal, a missing one is not
+    for journal in [Some(Vec::<u8>::new()), None] {
+        let mut data = serde_json::json!({ "seal": [], "status": "OK" });
+        if let Some(journal) = &journal {
+            data["journal"] = serde_json::json!(journal);
+        }
+        let result = serde_json::json!({ "type": "ProveResult", "data": data });
+        std::fs::write(file("result.json"), result.to_string()).unwrap();
+        assert_eq!(get_journal_from_result(&file("result.json")).ok(), journal);
+    }
+
+    // Eight words are only read from an array
+    let words: serde_json::Value = (0..8).map(|i| (i.to_string(), i)).collect();
+    std::fs::write(file("image_id.json"), words.to_string()).unwrap();
+    assert!(get_image_id(&file("image_id.json")).is_err());
+    std::fs::write(file("image_id.json"), "[0, 1, 2, 3, 4, 5, 6, 7]").unwrap();
+    assert_eq!(
+        get_image_id(&file("image_id.json")).unwrap(),
+        [0, 1, 2, 3, 4, 5, 6, 7]
+    );
+}
+
+// create-bundle only bundles a proof that verifies and no seal in the tree
+// does, so the bundle it would write is made with the library
+#[test]
+fn bundles_are_checked_when_read() {
+    let dir = temp_dir("bundle");
+    let file = |name: &str| dir.join(name).display().to_string();
+    let seal = write_seal(&dir, &generator_seal().to_vec());
+
+    let output = verifier(&[
+        "create-bundle",
+        "-i",
+        &image_id(),
+        "--journal",
+        "1,0,0,0",
+        "-s",
+        &seal,
+        "--bundle-out",
+        &file("bundle.json"),
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!dir.join("bundle.json").exists());
+
+    // A bundle is made from a journal, not read from one
+    let help = String::from_utf8(verifier(&["create-bundle", "--help"]).stdout).unwrap();
+    assert!(!help.contains("-b, --bundle"));
+
+    let words = get_image_id(&image_id()).unwrap();
+    let seal = generator_seal();
+    let bundle = ProofBundle::new(&words, &[1, 0, 0, 0], &seal).unwrap();
+    write_bundle(&bundle, &file("bundle.json")).unwrap();
+
+    let claim = json_output(&verifier(&[
+        "generate-claim",
+        "--bundle",
+        &file("bundle.json"),
+        "--json",
+    ]));
+    assert_eq!(claim["claim_digest"], CLAIM_DIGEST);
+    let input = json_output(&verifier(&[
+        "proof-as-input",
+        "--bundle",
+        &file("bundle.json"),
+        "--skip-verify",
+        "--json",
+    ]));
+    assert_eq!(input["journal"], "01000000");
+    // The proof of the bundle is still verified
+    let output = verifier(&["verify", "--bundle", &file("bundle.json")]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let mut image_id_words = words;
+    image_id_words[0] ^= 1;
+    // A.y of 3 instead of 2 puts A off the curve
+    let mut edited_seal = generator_seal().to_vec();
+    edited_seal[63] = 3;
+    for (field, value, error) in [
+        (
+            "journal",
+            serde_json::json!("02000000"),
+            "Invalid bundle: claim digest",
+        ),
+        (
+            "image_id",
+            serde_json::json!(image_id_words),
+            "Invalid bundle: claim digest",
+        ),
+        (
+            "seal",
+            serde_json::json!(hex::encode(&edited_seal)),
+            "Invalid seal: A",
+        ),
+    ] {
+        let mut bundle: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(file("bundle.json")).unwrap()).unwrap();
+        bundle[field] = value;
+        let edited = file(&format!("{}.json", field));
+        std::fs::write(&edited, bundle.to_string()).unwrap();
+
+        let output = verifier(&["proof-as-input", "--bundle", &edited, "--skip-verify"]);
+        assert_eq!(output.status.code(), Some(1), "{}", field);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(error), "{}: {}", field, stderr);
+    }
+}
+
+#[test]
+fn json_output_file() {
+    let dir = temp_dir("output-file");
+    let output_file = dir.join("claim.json").display().to_string();
+
+    let output = verifier(&[
+        "generate-claim",
+        "-i",
+        &image_id(),
+        "--journal",
+        "1,0,0,0",
+        "--json",
+        "-o",
+        &output_file,
+    ]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let claim: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&output_file).unwrap()).unwrap();
+    assert_eq!(claim["journal"], "01000000");
+
+    // The template commands write the header with --header, the result with -o
+    let template = dir.join("template.h").display().to_string();
+    let header = dir.join("constants.h").display().to_string();
+    let result_file = dir.join("template.json").display().to_string();
+    std::fs::write(&template, "journal").unwrap();
+    let output = verifier(&[
+        "template-proof",
+        "--journal",
+        "1,0,0,0",
+        "-s",
+        &write_seal(&dir, &generator_seal().to_vec()),
+        "--skip-verify",
+        "-t",
+        &template,
+        "--header",
+        &header,
+        "--json",
+        "-o",
+        &result_file,
+    ]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let result: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&result_file).unwrap()).unwrap();
+    assert_eq!(result["output"], header);
+    assert_eq!(result["verified"], false);
+    assert!(std::path::Path::new(&header).exists());
+}
+
+#[test]
+fn snarkjs_files_match_risc0() {
+    let dir = temp_dir("snarkjs");
+    let seal = write_seal(&dir, &generator_seal().to_vec());
+    let file = |name: &str| dir.join(name).display().to_string();
+
+    let result = json_output(&verifier(&[
+        "export-snarkjs",
+        "-i",
+        &image_id(),
+        "--journal",
+        "1,0,0,0",
+        "-s",
+        &seal,
+        "--skip-verify",
+        "--proof",
+        &file("proof.json"),
+        "--public",
+        &file("public.json"),
+        "--verification-key",
+        &file("vk.json"),
+        "--json",
+    ]));
+    assert_eq!(result["verified"], false);
+
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+    let proof: risc0_groth16::ProofJson = serde_json::from_str(&read("proof.json")).unwrap();
+    assert_eq!(
+        risc0_groth16::Seal::try_from(proof).unwrap().to_vec(),
+        generator_seal().to_vec()
+    );
+
+    let vk: risc0_groth16::VerifyingKeyJson = serde_json::from_str(&read("vk.json")).unwrap();
+    assert_eq!(
+        serde_json::to_value(vk.verifying_key().unwrap()).unwrap(),
+        serde_json::to_value(Groth16ReceiptVerifierParameters::default().verifying_key).unwrap()
+    );
+
+    // The claim digest halves are the third and fourth inputs
+    let public: Vec<String> = serde_json::from_str(&read("public.json")).unwrap();
+    let claim_digest = hex::decode(CLAIM_DIGEST).unwrap();
+    for (half, input) in claim_digest.chunks(16).zip(&public[2..4]) {
+        assert_eq!(
+            u128::from_le_bytes(half.try_into().unwrap()).to_string(),
+            *input
+        );
+    }
+
+    // The files are consistent with the claim, but the generator seal does not verify
+    let output = verifier(&[
+        "import-snarkjs",
+        "-i",
+        &image_id(),
+        "--journal",
+        "1,0,0,0",
+        "--proof",
+        &file("proof.json"),
+        "--public",
+        &file("public.json"),
+        "--verification-key",
+        &file("vk.json"),
+        "-s",
+        &file("imported.json"),
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!dir.join("imported.json").exists());
+}
+
+#[test]
+fn gnark_round_trip() {
+    let dir = temp_dir("gnark");
+    // The committed seal is the bare byte array, wrap it as a host result file
+    let committed = Path::new(env!("CARGO_MANIFEST_DIR")).join("../snark-seal.json");
+    let seal: Vec<u8> = serde_json::from_str(&std::fs::read_to_string(committed).unwrap()).unwrap();
+    let seal_file = write_seal(&dir, &seal);
+    let file = |name: &str| dir.join(name).display().to_string();
+
+    for (encoding, proof_size, vk_size) in [
+        ("compressed", 164, 9 * 32 + 3 * 64 + 3 * 4),
+        ("uncompressed", 324, 9 * 64 + 3 * 128 + 3 * 4),
+    ] {
+        let proof = file(&format!("{}-proof.bin", encoding));
+        let vk = file(&format!("{}-vk.bin", encoding));
+        let imported = file(&format!("{}-seal.json", encoding));
+
+        let result = json_output(&verifier(&[
+            "export-gnark",
+            "--journal",
+            "1,0,0,0",
+            "-s",
+            &seal_file,
+            "--skip-verify",
+            "--encoding",
+            encoding,
+            "--proof",
+            &proof,
+            "--verification-key",
+            &vk,
+            "--json",
+        ]));
+        assert_eq!(result["encoding"], encoding);
+
+        let proof_bytes = std::fs::read(&proof).unwrap();
+        assert_eq!(proof_bytes.len(), proof_size);
+        // Nine G1 points: alpha, beta, delta and the six IC points. Three G2
+        // points and three lengths
+        assert_eq!(std::fs::read(&vk).unwrap().len(), vk_size);
+        if encoding == "uncompressed" {
+            // Without flags gnark and risc0 lay out A, B and C the same way
+            assert_eq!(proof_bytes[..256], seal[..]);
+        }
+
+        json_output(&verifier(&[
+            "import-gnark",
+            "--journal",
+            "1,0,0,0",
+            "--proof",
+            &proof,
+            "--verification-key",
+            &vk,
+            "-s",
+            &imported,
+            "--skip-verify",
+            "--json",
+        ]));
+        let result: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&imported).unwrap()).unwrap();
+        assert_eq!(result["data"]["seal"], serde_json::json!(seal));
+    }
+}
+
+#[test]
+fn public_inputs_formats() {
+    let inputs = json_output(&verifier(&[
+        "public-inputs",
+        "-i",
+        &image_id(),
+        "--journal-u32",
+        "1",
+        "--json",
+    ]));
+    assert_eq!(inputs["claim_digest"], CLAIM_DIGEST);
+
+    let inputs = inputs["inputs"].as_array().unwrap();
+    assert_eq!(inputs.len(), 5);
+    let claim_digest = hex::decode(CLAIM_DIGEST).unwrap();
+    for (half, input) in claim_digest.chunks(16).zip(&inputs[2..4]) {
+        let value = u128::from_le_bytes(half.try_into().unwrap());
+        assert_eq!(input["decimal"], value.to_string());
+        assert_eq!(input["hex"], format!("{:064x}", value));
+        let c_bytes: Vec<String> = half
+            .iter()
+            .chain([0u8; 16].iter())
+            .map(u8::to_string)
+            .collect();
+        assert_eq!(input["c_bytes"], c_bytes.join(", "));
+    }
+}
+
+#[test]
+fn template_fixed_inputs_msm() {
+    let dir = temp_dir("fixed-msm");
+    let vk = snarkjs_verifying_key(&dir);
+    let ic: Vec<G1Affine> = vk["IC"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(g1_from_json)
+        .collect();
+
+    let inputs = json_output(&verifier(&[
+        "public-inputs",
+        "-i",
+        &image_id(),
+        "--journal-u32",
+        "1",
+        "--json",
+    ]));
+    let inputs: Vec<Fr> = inputs["inputs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|input| decimal(&input["decimal"]))
+        .collect();
+
+    let fixed = (ic[0].into_group() + ic[1] * inputs[0] + ic[2] * inputs[1] + ic[5] * inputs[4])
+        .into_affine();
+    let lines = template_setup(&dir, &["vk_fixed_msm", "vk_gamma_abc_0"], &[]);
+    assert_eq!(lines[0], g1_c_bytes(&fixed));
+    assert_eq!(lines[1], g1_c_bytes(&ic[0]));
+
+    // With the claim terms it is the whole input of the pairing with gamma
+    let all = ic[1..]
+        .iter()
+        .zip(&inputs)
+        .fold(ic[0].into_group(), |sum, (point, input)| {
+            sum + *point * input
+        });
+    assert_eq!(
+        (fixed + ic[3] * inputs[2] + ic[4] * inputs[3]).into_affine(),
+        all.into_affine()
+    );
+}
+
+#[test]
+fn template_alphabeta() {
+    let dir = temp_dir("alphabeta");
+    let vk = snarkjs_verifying_key(&dir);
+    let alphabeta = Bn254::pairing(
+        g1_from_json(&vk["vk_alpha_1"]),
+        g2_from_json(&vk["vk_beta_2"]),
+    )
+    .0;
+
+    let lines = template_setup(&dir, &["vk_alphabeta_12"], &[]);
+    let mut bytes = Vec::new();
+    for fq6 in [alphabeta.c0, alphabeta.c1] {
+        for fq2 in [fq6.c0, fq6.c1, fq6.c2] {
+            bytes.extend(fq2.c0.into_bigint().to_bytes_le());
+            bytes.extend(fq2.c1.into_bigint().to_bytes_le());
+        }
+    }
+    assert_eq!(bytes.len(), 12 * 32);
+    assert_eq!(lines[0], c_bytes(&bytes));
+}
+
+#[test]
+fn template_prepared_lines() {
+    let dir = temp_dir("prepared-lines");
+    let vk = snarkjs_verifying_key(&dir);
+    let lines = template_setup(
+        &dir,
+        &["g2_lines_count", "gamma_g2_lines", "delta_g2_lines"],
+        &["--prepared-lines"],
+    );
+    let count: usize = lines[0].parse().unwrap();
+
+    let g1 = G1Affine::generator() * Fr::from(7u64);
+    for (point, line) in [("vk_gamma_2", &lines[1]), ("vk_delta_2", &lines[2])] {
+        let bytes: Vec<u8> = line.split(", ").map(|byte| byte.parse().unwrap()).collect();
+        assert_eq!(bytes.len(), count * 3 * 64);
+
+        let fq2: Vec<Fq2> = bytes
+            .chunks(64)
+            .map(|c| {
+                Fq2::new(
+                    Fq::from_le_bytes_mod_order(&c[..32]),
+                    Fq::from_le_bytes_mod_order(&c[32..]),
+                )
+            })
+            .collect();
+        let prepared = G2Prepared::<ark_bn254::Config> {
+            ell_coeffs: fq2.chunks(3).map(|c| (c[0], c[1], c[2])).collect(),
+            infinity: false,
+        };
+
+        let pairing = Bn254::final_exponentiation(Bn254::multi_miller_loop([g1], [prepared]));
+        assert_eq!(
+            pairing.unwrap(),
+            Bn254::pairing(g1, g2_from_json(&vk[point]))
+        );
+    }
+}
+
+#[test]
+fn export_pairings_negates_a() {
+    let dir = temp_dir("pairings");
+    let seal = write_seal(&dir, &generator_seal().to_vec());
+    let pairings = json_output(&verifier(&[
+        "export-pairings",
+        "-i",
+        &image_id(),
+        "--journal",
+        "1,0,0,0",
+        "-s",
+        &seal,
+        "--skip-verify",
+        "--json",
+    ]));
+
+    // A and C are the generator (1, 2), -A is (1, p - 2) with an odd y
+    let one = format!("01{}", "00".repeat(31));
+    assert_eq!(pairings["c"], one);
+    assert_eq!(pairings["neg_a"], format!("01{}80", "00".repeat(30)));
+    assert_eq!(pairings["product_is_one"], false);
+}
+
+#[test]
+fn winternitz_signatures() {
+    let dir = temp_dir("winternitz");
+    let seal = write_seal(&dir, &generator_seal().to_vec());
+    let file = |name: &str| dir.join(name).display().to_string();
+
+    let input = json_output(&verifier(&[
+        "proof-as-input",
+        "--journal-result",
+        &seal,
+        "-s",
+        &seal,
+        "--skip-verify",
+        "--json",
+    ]));
+
+    for (hash, digit_bits, hash_size, digits) in [
+        // 4 bytes in 8 nibbles with a checksum of at most 120, 128 bytes in
+        // 256 nibbles with a checksum of at most 3840
+        ("sha256", "4", 32, [8 + 2, 256 + 3]),
+        // In octal digits the checksums are at most 77 and 2394
+        ("hash160", "3", 20, [11 + 3, 342 + 4]),
+    ] {
+        let key = file(&format!("{}-key.json", hash));
+        let public_keys = file(&format!("{}-public.json", hash));
+        let signatures = file(&format!("{}-signatures.json", hash));
+
+        let keygen = [
+            "winternitz-keygen",
+            "--hash",
+            hash,
+            "--digit-bits",
+            digit_bits,
+            "-k",
+            &key,
+            "-p",
+            &public_keys,
+            "--json",
+        ];
+        let result = json_output(&verifier(&keygen));
+        assert_eq!(result["journal_digits"], digits[0]);
+        assert_eq!(result["proof_digits"], digits[1]);
+        // The key is never replaced
+        assert_eq!(verifier(&keygen).status.code(), Some(74));
+
+        json_output(&verifier(&[
+            "winternitz-sign",
+            "-k",
+            &key,
+            "--journal-result",
+            &seal,
+            "-s",
+            &seal,
+            "--skip-verify",
+            "--signatures",
+            &signatures,
+            "--json",
+        ]));
+        // The key records the signed message and refuses any other
+        let key_file: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&key).unwrap()).unwrap();
+        assert_eq!(key_file["signed"].as_str().unwrap().len(), 64);
+        let other = file(&format!("{}-other.json", hash));
+        let sign_other = verifier(&[
+            "winternitz-sign",
+            "-k",
+            &key,
+            "--journal",
+            "2,0,0,0",
+            "-s",
+            &seal,
+            "--skip-verify",
+            "--signatures",
+            &other,
+        ]);
+        assert_eq!(sign_other.status.code(), Some(65));
+        assert!(String::from_utf8_lossy(&sign_other.stderr).contains("already signed"));
+        assert!(!Path::new(&other).exists());
+
+        let verify = [
+            "winternitz-verify",
+            "-p",
+            &public_keys,
+            "--signatures",
+            &signatures,
+            "--json",
+        ];
+        let result = json_output(&verifier(&verify));
+        assert_eq!(result["journal"], input["journal"]);
+        assert_eq!(result["proof"], input["proof"]);
+
+        let scripts = json_output(&verifier(&[
+            "winternitz-script",
+            "-p",
+            &public_keys,
+            "--signatures",
+            &signatures,
+            "--json",
+        ]));
+        // A signature and a digit per digit
+        let witness = scripts["proof"]["witness"].as_array().unwrap();
+        assert_eq!(witness.len(), 2 * digits[1]);
+
+        let mut signed: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&signatures).unwrap()).unwrap();
+        assert_eq!(
+            signed["proof"]["signature"][0].as_str().unwrap().len(),
+            2 * hash_size
+        );
+
+        // A raised digit no longer matches its signature nor the checksum
+        let digit = signed["proof"]["digits"][0].as_u64().unwrap();
+        let mut message = hex::decode(signed["proof"]["message"].as_str().unwrap()).unwrap();
+        message[0] |= 0x80;
+        signed["proof"]["message"] = hex::encode(&message).into();
+        signed["proof"]["digits"][0] =
+            (digit | 1 << (digit_bits.parse::<u32>().unwrap() - 1)).into();
+        std::fs::write(&signatures, signed.to_string()).unwrap();
+        assert_eq!(verifier(&verify).status.code(), Some(1));
+    }
+}
+
+#[test]
+fn witness_round_trip() {
+    let dir = temp_dir("witness");
+    let seal = write_seal(&dir, &generator_seal().to_vec());
+    let witness = dir.join("witness.json").display().to_string();
+    let imported = dir.join("imported.json").display().to_string();
+
+    // The journal in one element and the proof in two, or the 264 nibbles
+    // in an element each
+    for (packing, chunk_size, elements) in [("byte", "80", 3), ("nibble", "520", 264)] {
+        let export = json_output(&verifier(&[
+            "export-bitcoin-witness",
+            "--journal-result",
+            &seal,
+            "-s",
+            &seal,
+            "--packing",
+            packing,
+            "--chunk-size",
+            chunk_size,
+            "--skip-verify",
+            "--json",
+        ]));
+        assert_eq!(export["witness"].as_array().unwrap().len(), elements);
+        assert_eq!(export["standard"], true);
+        std::fs::write(&witness, export["witness"].to_string()).unwrap();
+
+        let import = json_output(&verifier(&[
+            "import-bitcoin-witness",
+            "-w",
+            &witness,
+            "--packing",
+            packing,
+            "-s",
+            &imported,
+            "--skip-verify",
+            "--json",
+        ]));
+        assert_eq!(import["journal"], "01000000");
+        let read = |name: &str| -> serde_json::Value {
+            serde_json::from_str(&std::fs::read_to_string(name).unwrap()).unwrap()
+        };
+        assert_eq!(read(&imported)["data"]["seal"], read(&seal)["data"]["seal"]);
+    }
+
+    // Nibbles read as bytes leave a proof that is not on the curve
+    let output = verifier(&[
+        "import-bitcoin-witness",
+        "-w",
+        &witness,
+        "-s",
+        &imported,
+        "--skip-verify",
+    ]);
+    assert!(!output.status.success());
+
+    let output = verifier(&[
+        "export-bitcoin-witness",
+        "--journal-result",
+        &seal,
+        "-s",
+        &seal,
+        "--chunk-size",
+        "521",
+        "--skip-verify",
+    ]);
+    assert_eq!(output.status.code(), Some(65));
+}
+
+#[test]
+fn merkle_commit_and_check() {
+    let dir = temp_dir("merkle");
+    let seal = write_seal(&dir, &generator_seal().to_vec());
+
+    // The 132 bytes of the proof input in 33 words of 4 bytes, padded to 64
+    // leaves
+    let tree = json_output(&verifier(&[
+        "merkle-commit",
+        "--journal-result",
+        &seal,
+        "-s",
+        &seal,
+        "--skip-verify",
+        "--json",
+    ]));
+    assert_eq!(tree["input"].as_str().unwrap().len(), 2 * 132);
+    assert_eq!(tree["depth"], 6);
+    let leaves = tree["leaves"].as_array().unwrap();
+    assert_eq!(leaves.len(), 33);
+    assert_eq!(leaves[0]["word"], "01000000");
+
+    let root = tree["root"].as_str().unwrap();
+    let check = |index: &str, word: &str, proof: &serde_json::Value| {
+        let proof: Vec<&str> = proof
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|sibling| sibling.as_str().unwrap())
+            .collect();
+        verifier(&[
+            "merkle-check",
+            "--root",
+            root,
+            "--index",
+            index,
+            "--word",
+            word,
+            "--proof",
+            &proof.join(","),
+            "--json",
+        ])
+    };
+
+    // A word of B, which differs from its neighbours
+    let leaf = &leaves[10];
+    let word = leaf["word"].as_str().unwrap();
+    assert_eq!(
+        json_output(&check("10", word, &leaf["proof"]))["valid"],
+        true
+    );
+    assert_eq!(
+        check("10", "ffffffff", &leaf["proof"]).status.code(),
+        Some(1)
+    );
+    assert_eq!(check("11", word, &leaf["proof"]).status.code(), Some(1));
+    assert_eq!(check("10", "0x", &leaf["proof"]).status.code(), Some(65));
+}
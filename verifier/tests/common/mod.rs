@@ -0,0 +1,33 @@
+// Helpers shared by the integration tests, each of which uses only some
+#![allow(dead_code)]
+
+use ark_bn254::{G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+use risc0_groth16::Seal;
+
+/// Big-endian bytes of a field element, as in the seal
+pub fn be<F: PrimeField>(fq: &F) -> Vec<u8> {
+    fq.into_bigint().to_bytes_be()
+}
+
+pub fn g1(point: &G1Affine) -> Vec<Vec<u8>> {
+    vec![be(&point.x), be(&point.y)]
+}
+
+/// The seal layout keeps the imaginary part of each G2 coordinate first
+pub fn g2(point: &G2Affine) -> Vec<Vec<Vec<u8>>> {
+    vec![
+        vec![be(&point.x.c1), be(&point.x.c0)],
+        vec![be(&point.y.c1), be(&point.y.c0)],
+    ]
+}
+
+/// Generators of G1 and G2: a seal of valid points that does not verify
+pub fn generator_seal() -> Seal {
+    Seal {
+        a: g1(&G1Affine::generator()),
+        b: g2(&G2Affine::generator()),
+        c: g1(&G1Affine::generator()),
+    }
+}
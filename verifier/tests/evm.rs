@@ -0,0 +1,52 @@
+mod common;
+
+use common::generator_seal;
+use risc0_zkp::core::digest::Digest;
+use risc0_zkvm::sha::Digestible;
+use risc0_zkvm::Groth16ReceiptVerifierParameters;
+use verifier::evm::{encode_seal, encode_verify_arguments, selector};
+
+// Digest of the default verifier parameters, pinned by risc0-zkvm. The RISC Zero
+// verifier router dispatches seals to the groth16 verifier of these parameters
+// by its first four bytes.
+const PARAMETERS_DIGEST: &str = "bb001d444841d70e8bc0c7d034b349044bf3cf0117afb702b2f1e898b7dd13cc";
+
+#[test]
+fn selector_is_the_parameters_digest_prefix() {
+    let params = Groth16ReceiptVerifierParameters::default();
+    assert_eq!(params.digest().to_string(), PARAMETERS_DIGEST);
+    assert_eq!(selector(&params), params.digest().as_bytes()[..4]);
+    assert_eq!(hex::encode(selector(&params)), "bb001d44");
+}
+
+#[test]
+fn seal_is_the_selector_and_the_proof_words() {
+    let params = Groth16ReceiptVerifierParameters::default();
+    let seal = encode_seal(&params, &generator_seal());
+
+    let words = hex::encode(generator_seal().to_vec());
+    assert_eq!(hex::encode(&seal), format!("bb001d44{}", words));
+}
+
+#[test]
+fn verify_arguments_are_abi_encoded() {
+    let params = Groth16ReceiptVerifierParameters::default();
+    let seal = encode_seal(&params, &generator_seal());
+    let image_id = Digest::from([0x11u8; 32]);
+    let journal_digest = Digest::from([0x22u8; 32]);
+
+    let arguments = encode_verify_arguments(&seal, &image_id, &journal_digest);
+    let words: Vec<String> = arguments.chunks(32).map(hex::encode).collect();
+
+    // Head: the seal offset after the three head words, then the two bytes32
+    assert_eq!(words[0], format!("{:064x}", 0x60));
+    assert_eq!(words[1], "11".repeat(32));
+    assert_eq!(words[2], "22".repeat(32));
+    // Tail: the seal length and the 260 seal bytes, padded to 288
+    assert_eq!(words[3], format!("{:064x}", 260));
+    assert_eq!(
+        words[4..].concat(),
+        format!("{}{}", hex::encode(&seal), "00".repeat(28))
+    );
+    assert_eq!(arguments.len(), 4 * 32 + 288);
+}
@@ -0,0 +1,24 @@
+use verifier::error::VerifierError;
+use verifier::format::{from_u256_bigint, to_fixed_array};
+
+#[test]
+fn fixed_arrays_refuse_values_over_32_bytes() {
+    let mut expected = [0u8; 32];
+    expected[30..].copy_from_slice(&[1, 2]);
+    assert_eq!(to_fixed_array(vec![1, 2]).unwrap(), expected);
+    assert_eq!(to_fixed_array(vec![7; 32]).unwrap(), [7; 32]);
+
+    assert!(matches!(
+        to_fixed_array(vec![1; 33]),
+        Err(VerifierError::MalformedInput(_))
+    ));
+    // 2^256 needs 33 bytes
+    assert!(matches!(
+        from_u256_bigint(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+        ),
+        Err(VerifierError::MalformedInput(_))
+    ));
+    assert!(from_u256_bigint("not a number").is_err());
+    assert_eq!(from_u256_bigint("258").unwrap()[30..], [1, 2]);
+}
@@ -0,0 +1,58 @@
+use verifier::merkle::{leaf_hash, node_hash, verify_proof, MerkleTree, EMPTY_LEAF};
+
+#[test]
+fn merkle_proofs_verify() {
+    let data: Vec<u8> = (0..36u8).collect();
+
+    for word_size in 1..=9 {
+        let tree = MerkleTree::new(&data, word_size).unwrap();
+        let leaves = data.len().div_ceil(word_size);
+        assert_eq!(tree.words().len(), leaves);
+        assert_eq!(1 << tree.depth(), leaves.next_power_of_two());
+
+        let root = tree.root();
+        for (index, word) in tree.words().iter().enumerate() {
+            assert_eq!(word.len(), word_size);
+            let proof = tree.proof(index).unwrap();
+            assert_eq!(proof.len(), tree.depth());
+            assert!(verify_proof(&root, index, word, &proof));
+
+            let mut other = word.clone();
+            other[0] ^= 1;
+            assert!(!verify_proof(&root, index, &other, &proof));
+            assert!(!verify_proof(&root, index ^ 1, word, &proof));
+            assert!(!verify_proof(
+                &root,
+                index + (1 << proof.len()),
+                word,
+                &proof
+            ));
+        }
+        assert!(tree.proof(leaves).is_err());
+    }
+}
+
+// 36 bytes in words of 8 bytes: four full words and a padded one, in a tree
+// of eight leaves
+#[test]
+fn merkle_tree_layout() {
+    let data: Vec<u8> = (0..36u8).collect();
+    let tree = MerkleTree::new(&data, 8).unwrap();
+    assert_eq!(tree.words()[4], [32, 33, 34, 35, 0, 0, 0, 0]);
+
+    let leaves: Vec<_> = tree.words().iter().map(|word| leaf_hash(word)).collect();
+    let empty = node_hash(&EMPTY_LEAF, &EMPTY_LEAF);
+    let left = node_hash(
+        &node_hash(&leaves[0], &leaves[1]),
+        &node_hash(&leaves[2], &leaves[3]),
+    );
+    let right = node_hash(&node_hash(&leaves[4], &EMPTY_LEAF), &empty);
+    assert_eq!(tree.root(), node_hash(&left, &right));
+
+    let single = MerkleTree::new(&data[..3], 8).unwrap();
+    assert_eq!(single.depth(), 0);
+    assert_eq!(single.root(), leaf_hash(&[0, 1, 2, 0, 0, 0, 0, 0]));
+
+    assert!(MerkleTree::new(&data, 0).is_err());
+    assert!(MerkleTree::new(&[], 4).is_err());
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::Groth16;
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use common::{g1, g2};
+use risc0_groth16::Seal;
+use verifier::pairing::PairingOperands;
+
+// Five public inputs like the risc0 circuit, each the square of a witness
+struct Squares {
+    roots: [Fr; 5],
+}
+
+impl ConstraintSynthesizer<Fr> for Squares {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        for root in self.roots {
+            let input = cs.new_input_variable(|| Ok(root * root))?;
+            let root = cs.new_witness_variable(|| Ok(root))?;
+            cs.enforce_constraint(lc!() + root, lc!() + root, lc!() + input)?;
+        }
+        Ok(())
+    }
+}
+
+fn seal(proof: &ark_groth16::Proof<Bn254>) -> Seal {
+    Seal {
+        a: g1(&proof.a),
+        b: g2(&proof.b),
+        c: g1(&proof.c),
+    }
+}
+
+#[test]
+fn multi_pairing_of_a_valid_proof_is_one() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let roots = [2u64, 3, 5, 7, 11].map(Fr::from);
+    let inputs: Vec<Fr> = roots.iter().map(|root| root * root).collect();
+
+    let (pk, vk) = Groth16::<Bn254>::setup(Squares { roots }, &mut rng).unwrap();
+    let proof = Groth16::<Bn254>::prove(&pk, Squares { roots }, &mut rng).unwrap();
+    assert!(Groth16::<Bn254>::verify(&vk, &inputs, &proof).unwrap());
+
+    let operands = PairingOperands::new(&vk, &seal(&proof), &inputs).unwrap();
+    assert_eq!(operands.neg_a, -proof.a);
+    assert!(operands.check());
+
+    // Any other input breaks the equation
+    let mut wrong = inputs.clone();
+    wrong[2] += Fr::from(1u64);
+    assert!(!PairingOperands::new(&vk, &seal(&proof), &wrong)
+        .unwrap()
+        .check());
+    assert!(PairingOperands::new(&vk, &seal(&proof), &inputs[..4]).is_err());
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use common::generator_seal;
+use verifier::bundle::{read_bundle, write_bundle, ProofBundle};
+use verifier::format::get_image_id;
+use verifier::signature::{
+    generate_key, read_key, sign_bundle, verify_signature, write_key, SignatureScheme,
+};
+use verifier::{generate_operator_key, verify_signed_bundle};
+
+const SCHEMES: [SignatureScheme; 2] = [SignatureScheme::Bip340, SignatureScheme::Ed25519];
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("signature-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Bundle of the committed image id with a seal of the generators, which does
+// not verify but passes the bundle checks
+fn bundle() -> ProofBundle {
+    let image_id =
+        get_image_id(&format!("{}/../image_id.json", env!("CARGO_MANIFEST_DIR"))).unwrap();
+    ProofBundle::new(&image_id, &[1, 0, 0, 0], &generator_seal()).unwrap()
+}
+
+#[test]
+fn signatures_round_trip() {
+    let dir = temp_dir("round-trip");
+    for scheme in SCHEMES {
+        let key = generate_key(scheme);
+        let mut signed = bundle();
+        let signature = sign_bundle(&signed, &key).unwrap();
+        assert_eq!(signature.scheme, scheme);
+        assert_eq!(signature.public_key, key.public_key);
+        assert!(verify_signature(&signed, &signature).is_ok());
+
+        // The signature is checked when the bundle is read back
+        let file = dir.join(format!("{}.json", scheme)).display().to_string();
+        signed.signature = Some(signature);
+        write_bundle(&signed, &file).unwrap();
+        let read = read_bundle(&file).unwrap();
+        assert_eq!(read.signature.unwrap().public_key, key.public_key);
+    }
+}
+
+#[test]
+fn tampered_bundles_are_rejected() {
+    let dir = temp_dir("tampered");
+    for scheme in SCHEMES {
+        let mut signed = bundle();
+        signed.signature = Some(sign_bundle(&signed, &generate_key(scheme)).unwrap());
+
+        // The seal is only covered by the signature, the bundle checks pass
+        let mut tampered = bundle();
+        tampered.seal.replace_range(..2, "ff");
+        let signature = signed.signature.clone().unwrap();
+        let error = verify_signature(&tampered, &signature).unwrap_err();
+        assert_eq!(error.exit_code(), 1);
+
+        let file = dir.join(format!("{}.json", scheme)).display().to_string();
+        tampered.signature = Some(signature);
+        write_bundle(&tampered, &file).unwrap();
+        let error = read_bundle(&file).err().unwrap();
+        assert!(
+            error.to_string().contains("operator signature"),
+            "{}",
+            error
+        );
+    }
+}
+
+#[test]
+fn wrong_operator_keys_are_rejected() {
+    let dir = temp_dir("wrong-key");
+    for scheme in SCHEMES {
+        let key = generate_key(scheme);
+        let other = generate_key(scheme);
+        let signed = bundle();
+
+        // A signature does not verify under another public key
+        let mut signature = sign_bundle(&signed, &key).unwrap();
+        signature.public_key = other.public_key.clone();
+        assert_eq!(
+            verify_signature(&signed, &signature)
+                .unwrap_err()
+                .exit_code(),
+            1
+        );
+
+        // A key file whose public key is not the one of its secret key
+        let mut mismatched = generate_key(scheme);
+        mismatched.public_key = other.public_key.clone();
+        assert_eq!(
+            sign_bundle(&signed, &mismatched).err().unwrap().exit_code(),
+            65
+        );
+
+        // A valid signature by an unexpected operator
+        let mut signed = bundle();
+        signed.signature = Some(sign_bundle(&signed, &key).unwrap());
+        let file = dir.join(format!("{}.json", scheme)).display().to_string();
+        write_bundle(&signed, &file).unwrap();
+        let error = verify_signed_bundle(&file, Some(&other.public_key))
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("signed by"), "{}", error);
+        assert_eq!(error.exit_code(), 1);
+
+        // The expected operator gets past the signature to the proof, which
+        // does not verify
+        let error = verify_signed_bundle(&file, Some(&key.public_key.to_uppercase()))
+            .err()
+            .unwrap();
+        assert!(!error.to_string().contains("signed by"), "{}", error);
+    }
+}
+
+#[test]
+fn wrong_schemes_are_rejected() {
+    for (scheme, other) in [
+        (SignatureScheme::Bip340, SignatureScheme::Ed25519),
+        (SignatureScheme::Ed25519, SignatureScheme::Bip340),
+    ] {
+        let signed = bundle();
+        let mut signature = sign_bundle(&signed, &generate_key(scheme)).unwrap();
+        signature.scheme = other;
+        assert_eq!(
+            verify_signature(&signed, &signature)
+                .unwrap_err()
+                .exit_code(),
+            1
+        );
+
+        let mut key = generate_key(scheme);
+        key.scheme = other;
+        assert!(sign_bundle(&signed, &key).is_err());
+    }
+}
+
+#[test]
+fn key_files_are_private_and_never_replaced() {
+    let dir = temp_dir("key-file");
+    let file = dir.join("key.json").display().to_string();
+
+    let output = generate_operator_key(SignatureScheme::Ed25519, &file).unwrap();
+    let key = read_key(&file).unwrap();
+    assert_eq!(key.public_key, output.public_key);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let error = write_key(&generate_key(SignatureScheme::Bip340), &file).unwrap_err();
+    assert_eq!(error.exit_code(), 74);
+    assert_eq!(read_key(&file).unwrap().secret_key, key.secret_key);
+}
@@ -0,0 +1,176 @@
+use bitcoin::absolute::LockTime;
+use bitcoin::key::{Secp256k1, XOnlyPublicKey};
+use bitcoin::taproot::{LeafVersion, TaprootBuilder};
+use bitcoin::transaction::Version;
+use bitcoin::{Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+use std::str::FromStr;
+use verifier::error::VerifierError;
+use verifier::tapscript::{leaf_script, witness};
+use verifier::winternitz::{WinternitzHash, WinternitzKey, WinternitzSignature};
+use verifier::witness::MAX_SCRIPT_ELEMENT_SIZE;
+
+// Internal key without a known secret key, from BIP341
+const NUMS: &str = "50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0";
+const AMOUNT: u64 = 10_000;
+
+// Spends a taproot output through the leaf with the given stack, and runs
+// the spend through the Bitcoin Core interpreter
+fn spend(leaf: &ScriptBuf, stack: &[Vec<u8>]) -> Result<(), bitcoinconsensus::Error> {
+    let secp = Secp256k1::verification_only();
+    let info = TaprootBuilder::new()
+        .add_leaf(0, leaf.clone())
+        .unwrap()
+        .finalize(&secp, XOnlyPublicKey::from_str(NUMS).unwrap())
+        .unwrap();
+    let script_pubkey = ScriptBuf::new_p2tr_tweaked(info.output_key());
+    let control_block = info
+        .control_block(&(leaf.clone(), LeafVersion::TapScript))
+        .unwrap();
+
+    let mut witness = Witness::new();
+    for element in stack {
+        witness.push(element);
+    }
+    witness.push(leaf.as_bytes());
+    witness.push(control_block.serialize());
+
+    let tx = Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness,
+        }],
+        output: vec![TxOut {
+            value: Amount::from_sat(AMOUNT / 2),
+            script_pubkey: ScriptBuf::new_op_return([]),
+        }],
+    };
+    let utxo = bitcoinconsensus::Utxo {
+        script_pubkey: script_pubkey.as_bytes().as_ptr(),
+        script_pubkey_len: script_pubkey.len() as u32,
+        value: AMOUNT as i64,
+    };
+
+    bitcoinconsensus::verify(
+        script_pubkey.as_bytes(),
+        AMOUNT,
+        &bitcoin::consensus::serialize(&tx),
+        Some(&[utxo]),
+        0,
+    )
+}
+
+fn proof() -> Vec<u8> {
+    (0..128u8).map(|i| i.wrapping_mul(37)).collect()
+}
+
+#[test]
+fn winternitz_scripts_accept_the_signatures() {
+    for (hash, digit_bits) in [
+        (WinternitzHash::Hash160, 4),
+        (WinternitzHash::Sha256, 4),
+        (WinternitzHash::Hash160, 3),
+        (WinternitzHash::Hash160, 8),
+    ] {
+        let mut key = WinternitzKey::generate(hash, digit_bits, 4);
+        let public_keys = key.public_keys().unwrap();
+        let signatures = key.sign_proof(&[1, 0, 0, 0], &proof()).unwrap();
+        // The key refuses another message, a copy of its seed signs it instead
+        let mut reused = WinternitzKey {
+            seed: key.seed.clone(),
+            ..WinternitzKey::generate(hash, digit_bits, 4)
+        };
+
+        for (params, public_key, signature) in [
+            (
+                key.journal_params().unwrap(),
+                &public_keys.journal,
+                &signatures.journal,
+            ),
+            (
+                key.proof_params().unwrap(),
+                &public_keys.proof,
+                &signatures.proof,
+            ),
+        ] {
+            let leaf = leaf_script(&params, public_key).unwrap();
+            let stack = witness(&params, signature).unwrap();
+            assert!(stack
+                .iter()
+                .all(|element| element.len() <= MAX_SCRIPT_ELEMENT_SIZE));
+            assert_eq!(spend(&leaf, &stack), Ok(()), "{:?} {}", hash, digit_bits);
+
+            // A digit taken from the signature of another message breaks it
+            let other = reused.sign_proof(&[0xff; 4], &[0xff; 128]).unwrap();
+            let other = match params.message_size {
+                4 => other.journal,
+                _ => other.proof,
+            };
+            assert_ne!(other.digits[0], signature.digits[0]);
+            let mut mixed = signature.clone();
+            mixed.signature[0] = other.signature[0].clone();
+            mixed.digits[0] = other.digits[0];
+            assert!(spend(&leaf, &witness(&params, &mixed).unwrap()).is_err());
+        }
+    }
+}
+
+#[test]
+fn winternitz_keys_sign_one_message() {
+    let mut key = WinternitzKey::generate(WinternitzHash::Sha256, 4, 4);
+    let first = key.sign_proof(&[1, 0, 0, 0], &proof()).unwrap();
+    assert!(key.signed.is_some());
+
+    // The same message reveals the same secrets
+    let again = key.sign_proof(&[1, 0, 0, 0], &proof()).unwrap();
+    assert_eq!(again.journal.signature, first.journal.signature);
+    assert_eq!(again.proof.signature, first.proof.signature);
+
+    for (journal, proof) in [([2, 0, 0, 0], proof()), ([1, 0, 0, 0], vec![0; 128])] {
+        assert!(matches!(
+            key.sign_proof(&journal, &proof),
+            Err(VerifierError::MalformedInput(_))
+        ));
+    }
+}
+
+// Anyone can hash a signature once more to raise its digit, only the checksum
+// catches it
+#[test]
+fn winternitz_scripts_check_the_checksum() {
+    let mut key = WinternitzKey::generate(WinternitzHash::Hash160, 4, 4);
+    let params = key.journal_params().unwrap();
+    let public_key = key.public_keys().unwrap().journal;
+    let signature = key.sign_proof(&[0x12, 0, 0, 0], &proof()).unwrap().journal;
+    let leaf = leaf_script(&params, &public_key).unwrap();
+    assert_eq!(spend(&leaf, &witness(&params, &signature).unwrap()), Ok(()));
+
+    let raised = WinternitzSignature {
+        digits: [
+            vec![signature.digits[0] + 1],
+            signature.digits[1..].to_vec(),
+        ]
+        .concat(),
+        signature: [
+            vec![hex::encode(
+                WinternitzHash::Hash160.chain(&hex::decode(&signature.signature[0]).unwrap(), 1),
+            )],
+            signature.signature[1..].to_vec(),
+        ]
+        .concat(),
+        ..signature.clone()
+    };
+    assert!(spend(&leaf, &witness(&params, &raised).unwrap()).is_err());
+}
+
+// With one bit per digit the witness of the proof exceeds the stack limit
+#[test]
+fn winternitz_scripts_fit_the_stack() {
+    let key = WinternitzKey::generate(WinternitzHash::Hash160, 1, 4);
+    let public_keys = key.public_keys().unwrap();
+    assert!(leaf_script(&key.journal_params().unwrap(), &public_keys.journal).is_ok());
+    assert!(leaf_script(&key.proof_params().unwrap(), &public_keys.proof).is_err());
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use ark_bn254::{G1Projective, G2Projective};
+use ark_ec::CurveGroup;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use ark_std::UniformRand;
+use common::{g1, g2};
+use risc0_groth16::Seal;
+use verifier::format::seal_from_c_bytes;
+use verifier::generate_proof_bytes_from_seal;
+use verifier::witness::{is_standard, pack, parse_witness, unpack, witness, WitnessPacking};
+
+// Half of the random points have an odd y, so both flags are covered
+#[test]
+fn c_bytes_round_trip() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..16 {
+        let a = G1Projective::rand(&mut rng).into_affine();
+        let b = G2Projective::rand(&mut rng).into_affine();
+        let c = G1Projective::rand(&mut rng).into_affine();
+        let seal = Seal {
+            a: g1(&a),
+            b: g2(&b),
+            c: g1(&c),
+        };
+
+        let proof = generate_proof_bytes_from_seal(seal.clone()).concat();
+        assert_eq!(seal_from_c_bytes(&proof).unwrap(), seal);
+
+        // Only x.c1 carries the flag of B, the top bit of x.c0 is not dropped
+        let mut flagged = proof.clone();
+        flagged[63] |= 128;
+        assert!(seal_from_c_bytes(&flagged).is_err());
+    }
+
+    assert!(seal_from_c_bytes(&[0u8; 127]).is_err());
+    // x = 0 has no point on G1
+    assert!(seal_from_c_bytes(&[0u8; 128]).is_err());
+}
+
+#[test]
+fn witness_round_trip() {
+    let journal = [1u8, 0, 0, 0];
+    let proof: Vec<u8> = (0..128u8).map(|i| i.wrapping_mul(37)).collect();
+
+    for packing in [WitnessPacking::Byte, WitnessPacking::Nibble] {
+        for chunk_size in [1, 7, 80, 520] {
+            let stack = witness(&journal, &proof, packing, chunk_size).unwrap();
+            assert!(stack.iter().all(|element| element.len() <= chunk_size));
+            if packing == WitnessPacking::Nibble {
+                assert!(stack
+                    .iter()
+                    .all(|element| matches!(element.as_slice(), [] | [1..=15])));
+            }
+            // The first element of the journal is on top
+            assert_eq!(
+                stack.last(),
+                pack(&journal, packing, chunk_size).unwrap().first()
+            );
+            assert_eq!(
+                parse_witness(&stack, packing, 128).unwrap(),
+                (journal.to_vec(), proof.clone())
+            );
+        }
+    }
+
+    // A nibble an element, zero as the empty element
+    assert_eq!(
+        pack(&[0xa5, 0x0c], WitnessPacking::Nibble, 3).unwrap(),
+        vec![vec![0xa], vec![0x5], vec![], vec![0xc]]
+    );
+    assert!(pack(&journal, WitnessPacking::Byte, 0).is_err());
+    assert!(pack(&journal, WitnessPacking::Byte, 521).is_err());
+    assert!(unpack(&[vec![0x10], vec![]], WitnessPacking::Nibble).is_err());
+    assert!(unpack(&[vec![0x1]], WitnessPacking::Nibble).is_err());
+    // Zero is not minimally encoded as 0x00, nor two nibbles as one element
+    assert!(unpack(&[vec![0x0], vec![0x1]], WitnessPacking::Nibble).is_err());
+    assert!(unpack(&[vec![0x1, 0x2], vec![]], WitnessPacking::Nibble).is_err());
+    assert!(parse_witness(&[journal.to_vec()], WitnessPacking::Byte, 128).is_err());
+
+    // Tapscript elements are only bound by the 520-byte consensus limit
+    assert!(is_standard(&[vec![0; 81], vec![0; 520]]));
+    assert!(!is_standard(&[vec![0; 521]]));
+    assert!(!is_standard(&vec![vec![0]; 1001]));
+}
@@ -1,9 +1,29 @@
 use serde::{Deserialize, Serialize};
 
+// Process exit codes shared by the host and the verifier
+/// Process exit code when the proof does not verify
+pub const EXIT_INVALID_PROOF: u8 = 1;
+/// Process exit code when an input can not be parsed (sysexits EX_DATAERR)
+pub const EXIT_MALFORMED_INPUT: u8 = 65;
+/// Process exit code when the prover fails (sysexits EX_SOFTWARE)
+pub const EXIT_PROVER: u8 = 70;
+/// Process exit code when a file can not be read or written (sysexits EX_IOERR)
+pub const EXIT_IO: u8 = 74;
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
 pub enum ResultType {
-    ProveResult { seal: Vec<u8>, status: String },
+    ProveResult {
+        seal: Vec<u8>,
+        status: String,
+        /// Journal the seal was proven for, missing in results written before
+        /// it was recorded and in failed results
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        journal: Option<Vec<u8>>,
+        /// Prover options the result was produced with
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        options: Option<serde_json::Value>,
+    },
 }
 
 impl ResultType {
@@ -12,7 +32,7 @@ impl ResultType {
             .map_err(|_| format!("Failed to parse JSON string: {}", json))?;
 
         let result: Self = serde_json::from_value(value)
-            .map_err(|_| format!("Failed to deserialize JSON value"))?;
+            .map_err(|_| "Failed to deserialize JSON value".to_string())?;
 
         Ok(result)
     }
@@ -23,6 +43,18 @@ impl ResultType {
         }
     }
 
+    pub fn get_journal(&self) -> Option<Vec<u8>> {
+        match self {
+            ResultType::ProveResult { journal, .. } => journal.clone(),
+        }
+    }
+
+    pub fn get_options(&self) -> Option<serde_json::Value> {
+        match self {
+            ResultType::ProveResult { options, .. } => options.clone(),
+        }
+    }
+
     pub fn get_status(&self) -> String {
         match self {
             ResultType::ProveResult { status, .. } => status.clone(),